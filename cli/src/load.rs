@@ -5,20 +5,34 @@ use crate::status::{
 };
 use zbus::Connection;
 
-/// Sends the dbus command to load a bitstream
+/// Bit for partial reconfiguration in the reconfiguration-flags bitfield, kept in
+/// step with `ReconfigFlags::PARTIAL_RECONFIG` on the daemon side.
+const PARTIAL_RECONFIG: u32 = 0x1;
+
+/// Sends the dbus command to load a bitstream through the unified `program_fpga`
+/// call, which bundles the image source and the reconfiguration-flags bitfield
+/// into a single atomic request so the flags and the bitstream are applied under
+/// one lock. The image is passed as a `"path"` source resolved under the
+/// firmware dir. `partial` selects the partial-reconfiguration bit; callers need
+/// not assemble the bitmask by hand.
 async fn call_load_bitstream(
     platform_str: &str,
     device_handle: &str,
     file_path: &str,
+    partial: bool,
 ) -> Result<String, zbus::Error> {
+    let flags = if partial { PARTIAL_RECONFIG } else { 0 };
     let connection = Connection::system().await?;
     let proxy = control_proxy::ControlProxy::new(&connection).await?;
     proxy
-        .write_bitstream_direct(platform_str, device_handle, file_path)
+        .program_fpga(platform_str, device_handle, "path", file_path, vec![], flags)
         .await
 }
 
-/// Sends the dbus command to apply an overlay
+/// Sends the dbus command to apply an overlay. The daemon's `apply_overlay`
+/// ensures that any bitstream named by the overlay's `firmware-name` property is
+/// programmed onto the manager the overlay's `fpga-mgr` phandle points at before
+/// the overlay is applied, matching the kernel's "program then overlay" ordering.
 async fn call_apply_overlay(
     platform: &str,
     file_path: &str,
@@ -27,7 +41,7 @@ async fn call_apply_overlay(
     let connection = Connection::system().await?;
     let proxy = control_proxy::ControlProxy::new(&connection).await?;
     proxy
-        .apply_overlay(platform, overlay_handle, file_path)
+        .apply_overlay(platform, overlay_handle, file_path, "")
         .await
 }
 
@@ -74,16 +88,74 @@ async fn apply_overlay(
     call_apply_overlay(&platform, file_path, &overlay_handle_to_use).await
 }
 
-/// Populates the device_handle appropriately before calling `call_load_bitstream`
+/// Verifies and loads a firmware package (CAB) through the daemon.
+async fn load_package(
+    device_handle: &Option<String>,
+    file_path: &str,
+    partial: bool,
+) -> Result<String, zbus::Error> {
+    let dev = match device_handle {
+        None => get_first_device_handle().await?,
+        Some(dev) => dev.clone(),
+    };
+    let flags = if partial { "partial" } else { "" };
+    let connection = Connection::system().await?;
+    let proxy = control_proxy::ControlProxy::new(&connection).await?;
+    proxy
+        .load_firmware_package("", &dev, file_path, flags)
+        .await
+}
+
+/// Sends the dbus command to load a bitstream after integrity verification. The
+/// expected digest and/or detached signature are forwarded to the daemon, which
+/// checks them against the resolved file and its trusted keys before any bytes
+/// reach the FPGA manager.
+async fn call_load_bitstream_verified(
+    device_handle: &str,
+    file_path: &str,
+    digest: &str,
+    signature: Vec<u8>,
+    policy: &str,
+) -> Result<String, zbus::Error> {
+    let connection = Connection::system().await?;
+    let proxy = control_proxy::ControlProxy::new(&connection).await?;
+    proxy
+        .write_bitstream_verified("", device_handle, file_path, "", digest, signature, policy)
+        .await
+}
+
+/// Populates the device_handle appropriately before calling `call_load_bitstream`.
+/// When any verification option is supplied the image is routed through the
+/// verifying load path instead.
 async fn load_bitstream(
     device_handle: &Option<String>,
     file_path: &str,
+    partial: bool,
+    digest: &Option<String>,
+    signature: &Option<String>,
+    verify_policy: &Option<String>,
 ) -> Result<String, zbus::Error> {
     let dev = match device_handle {
         None => get_first_device_handle().await?,
         Some(dev) => dev.clone(),
     };
-    call_load_bitstream("", &dev, file_path).await
+    if digest.is_some() || signature.is_some() || verify_policy.is_some() {
+        let signature_bytes = match signature {
+            Some(path) => std::fs::read(path)
+                .map_err(|e| zbus::Error::Failure(format!("could not read signature {path}: {e}")))?,
+            None => Vec::new(),
+        };
+        let policy = verify_policy.as_deref().unwrap_or("enforce");
+        return call_load_bitstream_verified(
+            &dev,
+            file_path,
+            digest.as_deref().unwrap_or(""),
+            signature_bytes,
+            policy,
+        )
+        .await;
+    }
+    call_load_bitstream("", &dev, file_path, partial).await
 }
 
 /// Argument parser for the load command
@@ -95,6 +167,25 @@ pub async fn load_handler(
         LoadSubcommand::Overlay { file, handle } => {
             apply_overlay(dev_handle, file.as_ref(), handle).await
         }
-        LoadSubcommand::Bitstream { file } => load_bitstream(dev_handle, file.as_ref()).await,
+        LoadSubcommand::Bitstream {
+            file,
+            partial,
+            digest,
+            signature,
+            verify_policy,
+        } => {
+            load_bitstream(
+                dev_handle,
+                file.as_ref(),
+                *partial,
+                digest,
+                signature,
+                verify_policy,
+            )
+            .await
+        }
+        LoadSubcommand::Package { file, partial } => {
+            load_package(dev_handle, file.as_ref(), *partial).await
+        }
     }
 }
@@ -52,6 +52,35 @@ enum LoadSubcommand {
     Bitstream {
         /// Bitstream `FILE` to be loaded (typically .bit.bin)
         file: String,
+
+        /// Load the image as a partial-reconfiguration bitstream instead of a
+        /// full one, without having to compute the raw flag bitmask.
+        #[arg(long = "partial")]
+        partial: bool,
+
+        /// Expected lowercase-hex SHA-256 of the image; the load is rejected if
+        /// the resolved file does not match.
+        #[arg(long = "digest")]
+        digest: Option<String>,
+
+        /// `FILE` holding a detached signature to verify against fpgad's trusted
+        /// keys before programming.
+        #[arg(long = "signature")]
+        signature: Option<String>,
+
+        /// Verification policy: `enforce` (default) aborts on a failed check,
+        /// `warn-only` logs it and proceeds.
+        #[arg(long = "verify-policy")]
+        verify_policy: Option<String>,
+    },
+    /// Load a verified firmware package (CAB) into the system
+    Package {
+        /// Firmware package `FILE` to verify and load (a CAB archive)
+        file: String,
+
+        /// Load the package payload as a partial-reconfiguration bitstream.
+        #[arg(long = "partial")]
+        partial: bool,
     },
 }
 
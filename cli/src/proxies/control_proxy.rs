@@ -40,7 +40,62 @@ pub trait Control {
         firmware_lookup_path: &str,
     ) -> Result<String>;
 
+    async fn write_bitstream_verified(
+        &self,
+        platform_string: &str,
+        device_handle: &str,
+        bitstream_path_str: &str,
+        firmware_lookup_path: &str,
+        expected_digest: &str,
+        signature: Vec<u8>,
+        policy: &str,
+    ) -> Result<String>;
+
+    async fn program_fpga(
+        &self,
+        platform_string: &str,
+        device_handle: &str,
+        source_kind: &str,
+        source: &str,
+        bytes: Vec<u8>,
+        flags: u32,
+    ) -> Result<String>;
+
+    async fn load_firmware_package(
+        &self,
+        platform_string: &str,
+        device_handle: &str,
+        package_path: &str,
+        flags: &str,
+    ) -> Result<String>;
+
+    async fn write_bitstream_fd(
+        &self,
+        platform_string: &str,
+        device_handle: &str,
+        image: zbus::zvariant::OwnedFd,
+        name: &str,
+    ) -> Result<String>;
+
+    async fn write_bitstream_monitored(
+        &self,
+        platform_string: &str,
+        device_handle: &str,
+        firmware_rel: &str,
+        flags: u32,
+    ) -> Result<String>;
+
+    async fn flash_update(&self, sec_mgr_handle: &str, filename: &str) -> Result<String>;
+
     async fn remove_overlay(&self, platform_str: &str, overlay_handle: &str) -> Result<String>;
 
     async fn write_property(&self, property_path_str: &str, data: &str) -> Result<String>;
+
+    /// Progress updates emitted while a monitored load is in flight.
+    #[zbus(signal)]
+    fn load_progress(&self, device_handle: &str, phase: &str, percent: u8) -> Result<()>;
+
+    /// Terminal signal carrying the outcome of a monitored load.
+    #[zbus(signal)]
+    fn load_finished(&self, device_handle: &str, success: bool, error_code: i32) -> Result<()>;
 }
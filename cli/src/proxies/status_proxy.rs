@@ -16,4 +16,6 @@ pub trait Status {
     async fn get_platform_type(&self, device_handle: &str) -> Result<String>;
     async fn get_platform_types(&self) -> Result<String>;
     async fn get_platform_name(&self, _device_handle: &str) -> Result<String>;
+    async fn get_bridge_state(&self, bridge_handle: &str) -> Result<String>;
+    async fn dfx_get_rm_info(&self) -> Result<String>;
 }
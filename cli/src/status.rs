@@ -30,6 +30,15 @@ pub async fn call_get_fpga_state(device_handle: &str) -> Result<String, zbus::Er
     proxy.get_fpga_state("", device_handle).await
 }
 
+/// Queries the Xilinx DFX manager for its reconfigurable-module occupancy table.
+/// This is only available on dynamic-function-exchange platforms with the
+/// `softeners` feature enabled, so callers treat an error as "not applicable".
+async fn call_dfx_get_rm_info() -> Result<String, zbus::Error> {
+    let connection = Connection::system().await?;
+    let proxy = status_proxy::StatusProxy::new(&connection).await?;
+    proxy.dfx_get_rm_info().await
+}
+
 /// Sends the dbus command to get the platform_compat_string for a given device
 pub async fn call_get_platform_type(device_handle: &str) -> Result<String, zbus::Error> {
     let connection = Connection::system().await?;
@@ -141,6 +150,17 @@ async fn get_full_status_message() -> Result<String, zbus::Error> {
         let status = call_get_overlay_status(&p, &overlay).await?;
         ret_string.push_str(format!("| {overlay} | {status} |\n").as_ref());
     }
+
+    // Xilinx DFX platforms carry dynamic-function-exchange state the sysfs view
+    // cannot show; render it when the daemon can reach dfx-mgr, and stay silent
+    // otherwise so non-DFX systems are unaffected.
+    if let Ok(rm_info) = call_dfx_get_rm_info().await {
+        if !rm_info.trim().is_empty() {
+            ret_string += "\n---- DFX SLOTS ----\n";
+            ret_string += rm_info.trim_end();
+            ret_string.push('\n');
+        }
+    }
     Ok(ret_string)
 }
 
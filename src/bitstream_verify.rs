@@ -0,0 +1,239 @@
+// This file is part of fpgad, an application to manage FPGA subsystem together with device-tree and kernel modules.
+//
+// Copyright 2025 Canonical Ltd.
+//
+// SPDX-License-Identifier: GPL-3.0-only
+//
+// fpgad is free software: you can redistribute it and/or modify it under the terms of the GNU General Public License version 3, as published by the Free Software Foundation.
+//
+// fpgad is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY; without even the implied warranties of MERCHANTABILITY, SATISFACTORY QUALITY, or FITNESS FOR A PARTICULAR PURPOSE.  See the GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License along with this program.  If not, see http://www.gnu.org/licenses/.
+
+//! Signature and hash verification of bitstreams.
+//!
+//! Before a resolved firmware file is handed to the FPGA manager the caller may
+//! supply an expected SHA-256 digest and/or a detached signature. The digest is
+//! recomputed from the file under the firmware source directory and compared,
+//! and the signature is checked against the set of PEM public keys loaded from
+//! [`config::TRUSTED_KEYS_DIR`] the first time a signature is checked and kept
+//! for the life of the process; keys added later are not picked up. The [`VerificationPolicy`] decides
+//! whether a mismatch aborts the load ([`VerificationPolicy::Enforce`]) or is
+//! only logged ([`VerificationPolicy::WarnOnly`]), letting operators roll
+//! verification out across a fleet gradually.
+
+use crate::config;
+use crate::error::FpgadError;
+use log::{info, trace, warn};
+use rsa::RsaPublicKey;
+use rsa::pkcs1v15::Pkcs1v15Sign;
+use rsa::pkcs8::DecodePublicKey;
+use sha2::{Digest, Sha256};
+use std::path::Path;
+use std::sync::OnceLock;
+
+/// Whether a failed check aborts programming or is merely logged.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum VerificationPolicy {
+    /// Reject the load on any verification failure.
+    Enforce,
+    /// Log the failure but allow the load to proceed.
+    WarnOnly,
+}
+
+impl VerificationPolicy {
+    /// Parses the symbolic policy name used on the wire, defaulting to
+    /// [`VerificationPolicy::Enforce`] for the empty string so that callers who
+    /// do not pass a policy get the safe behavior.
+    pub fn parse(s: &str) -> Result<VerificationPolicy, FpgadError> {
+        match s.trim() {
+            "" | "enforce" => Ok(VerificationPolicy::Enforce),
+            "warn" | "warn-only" => Ok(VerificationPolicy::WarnOnly),
+            other => Err(FpgadError::Argument(format!(
+                "unknown verification policy '{other}'"
+            ))),
+        }
+    }
+}
+
+static TRUSTED_KEYS: OnceLock<Vec<RsaPublicKey>> = OnceLock::new();
+
+/// Loads every `*.pem` public key from [`config::TRUSTED_KEYS_DIR`]. A directory
+/// that is missing or holds no readable keys yields an empty set, which means no
+/// signature can ever be trusted; unreadable entries are skipped with a warning
+/// rather than failing the whole daemon.
+fn load_trusted_keys() -> Vec<RsaPublicKey> {
+    let dir = Path::new(config::TRUSTED_KEYS_DIR);
+    let entries = match std::fs::read_dir(dir) {
+        Ok(entries) => entries,
+        Err(e) => {
+            warn!("no trusted-keys directory {dir:?}: {e}");
+            return Vec::new();
+        }
+    };
+    let mut keys = Vec::new();
+    for entry in entries.flatten() {
+        let path = entry.path();
+        if path.extension().and_then(|e| e.to_str()) != Some("pem") {
+            continue;
+        }
+        match std::fs::read_to_string(&path) {
+            Ok(pem) => match RsaPublicKey::from_public_key_pem(&pem) {
+                Ok(key) => {
+                    trace!("loaded trusted key {path:?}");
+                    keys.push(key);
+                }
+                Err(e) => warn!("ignoring malformed public key {path:?}: {e}"),
+            },
+            Err(e) => warn!("could not read trusted key {path:?}: {e}"),
+        }
+    }
+    info!("loaded {} trusted signing key(s)", keys.len());
+    keys
+}
+
+fn trusted_keys() -> &'static [RsaPublicKey] {
+    TRUSTED_KEYS.get_or_init(load_trusted_keys)
+}
+
+/// Lowercase-hex SHA-256 of `bytes`.
+fn sha256_hex(bytes: &[u8]) -> String {
+    Sha256::digest(bytes)
+        .iter()
+        .map(|b| format!("{b:02x}"))
+        .collect()
+}
+
+/// Returns true when `signature` is a valid PKCS#1 v1.5 SHA-256 signature over
+/// `digest` for any one of the trusted keys.
+fn signature_is_trusted(digest: &[u8], signature: &[u8]) -> bool {
+    trusted_keys()
+        .iter()
+        .any(|key| key.verify(Pkcs1v15Sign::new::<Sha256>(), digest, signature).is_ok())
+}
+
+/// Verifies the file at `firmware_abs` against an optional expected digest and an
+/// optional detached signature. Under [`VerificationPolicy::Enforce`] any failure
+/// returns [`FpgadError::Verification`] so no bytes ever reach the manager; under
+/// [`VerificationPolicy::WarnOnly`] the failure is logged and `Ok` is returned so
+/// the load proceeds.
+pub fn verify_bitstream(
+    firmware_abs: &Path,
+    expected_digest: Option<&str>,
+    signature: Option<&[u8]>,
+    policy: VerificationPolicy,
+) -> Result<(), FpgadError> {
+    let bytes = std::fs::read(firmware_abs).map_err(|e| FpgadError::IORead {
+        file: firmware_abs.to_path_buf(),
+        e,
+    })?;
+    let digest = Sha256::digest(&bytes);
+
+    if let Some(expected) = expected_digest.filter(|d| !d.trim().is_empty()) {
+        let actual = sha256_hex(&bytes);
+        if !actual.eq_ignore_ascii_case(expected.trim()) {
+            return fail(
+                policy,
+                format!("digest {actual} does not match expected {expected}"),
+            );
+        }
+        trace!("digest for {firmware_abs:?} matches expected");
+    }
+
+    if let Some(signature) = signature.filter(|s| !s.is_empty()) {
+        if !signature_is_trusted(&digest, signature) {
+            return fail(
+                policy,
+                format!("signature for {firmware_abs:?} is not trusted"),
+            );
+        }
+        trace!("signature for {firmware_abs:?} verified against a trusted key");
+    }
+
+    Ok(())
+}
+
+/// Turns a verification failure into either an error or a warning depending on
+/// the policy.
+fn fail(policy: VerificationPolicy, reason: String) -> Result<(), FpgadError> {
+    match policy {
+        VerificationPolicy::Enforce => Err(FpgadError::Verification(reason)),
+        VerificationPolicy::WarnOnly => {
+            warn!("verification failed (warn-only, proceeding): {reason}");
+            Ok(())
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+
+    fn write_temp(name: &str, bytes: &[u8]) -> std::path::PathBuf {
+        let path = std::env::temp_dir().join(format!(
+            "fpgad-bitstream-verify-test-{}-{name}",
+            std::process::id()
+        ));
+        std::fs::File::create(&path).unwrap().write_all(bytes).unwrap();
+        path
+    }
+
+    #[test]
+    fn verify_bitstream_rejects_digest_mismatch_under_enforce() {
+        let path = write_temp("digest-mismatch-enforce", b"bitstream-bytes");
+
+        let err = verify_bitstream(&path, Some(&"0".repeat(64)), None, VerificationPolicy::Enforce)
+            .unwrap_err();
+
+        assert!(matches!(err, FpgadError::Verification(_)));
+        let _ = std::fs::remove_file(&path);
+    }
+
+    #[test]
+    fn verify_bitstream_warns_but_proceeds_on_digest_mismatch() {
+        let path = write_temp("digest-mismatch-warn", b"bitstream-bytes");
+
+        let result =
+            verify_bitstream(&path, Some(&"0".repeat(64)), None, VerificationPolicy::WarnOnly);
+
+        assert!(result.is_ok());
+        let _ = std::fs::remove_file(&path);
+    }
+
+    #[test]
+    fn verify_bitstream_accepts_a_matching_digest() {
+        let bytes = b"bitstream-bytes";
+        let path = write_temp("digest-match", bytes);
+        let digest = sha256_hex(bytes);
+
+        let result = verify_bitstream(&path, Some(&digest), None, VerificationPolicy::Enforce);
+
+        assert!(result.is_ok());
+        let _ = std::fs::remove_file(&path);
+    }
+
+    #[test]
+    fn verify_bitstream_rejects_a_signature_with_no_trusted_keys_loaded() {
+        let bytes = b"bitstream-bytes";
+        let path = write_temp("signature-untrusted", bytes);
+
+        // No keys exist under config::TRUSTED_KEYS_DIR in the test environment,
+        // so any signature must be rejected rather than trusted by default.
+        let err = verify_bitstream(&path, None, Some(b"not-a-real-signature"), VerificationPolicy::Enforce)
+            .unwrap_err();
+
+        assert!(matches!(err, FpgadError::Verification(_)));
+        let _ = std::fs::remove_file(&path);
+    }
+
+    #[test]
+    fn parse_accepts_empty_string_as_enforce() {
+        assert_eq!(VerificationPolicy::parse("").unwrap(), VerificationPolicy::Enforce);
+    }
+
+    #[test]
+    fn parse_rejects_unknown_policy_name() {
+        assert!(VerificationPolicy::parse("yolo").is_err());
+    }
+}
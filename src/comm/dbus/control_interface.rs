@@ -10,21 +10,39 @@
 //
 // You should have received a copy of the GNU General Public License along with this program.  If not, see http://www.gnu.org/licenses/.
 
+use crate::bitstream_verify;
 use crate::config::FPGA_MANAGERS_DIR;
 use crate::error::FpgadError;
-use crate::platforms::platform::{platform_for_known_platform, platform_from_compat_or_device};
+use crate::firmware_package;
+use crate::flash_update;
+use crate::platforms::platform::{
+    ReconfigFlags, platform_for_known_platform, platform_from_compat_or_device,
+};
+use crate::platforms::platform::{Bridge, Fpga, FpgaImageInfo};
+use crate::platforms::universal_components::chained_load;
+use crate::platforms::universal_components::fdt;
+use crate::platforms::universal_components::fpga_region::{self, RegionRequest};
+use crate::platforms::universal_components::overlay_transaction;
+use crate::platforms::universal_components::universal_bridge::{self, UniversalBridge};
+use crate::platforms::universal_components::universal_overlay_handler::strip_internal_flags;
+use crate::platforms::universal_components::xclbin;
+#[cfg(feature = "softeners")]
+use crate::softeners::xilinx_dfx_mgr;
+use crate::config::system_config;
 use crate::system_io::{
-    extract_path_and_filename, fs_write, validate_device_handle, write_firmware_source_dir,
+    extract_path_and_filename, fs_append_bytes, fs_write, fs_write_bytes, maybe_decompress,
+    validate_device_handle, write_firmware_source_dir,
 };
 use log::trace;
+use std::fs::remove_file;
 use std::path::{Component, Path, PathBuf};
 use std::sync::Arc;
 use tokio::sync::{Mutex, MutexGuard, OnceCell};
-use zbus::{fdo, interface};
+use zbus::{SignalContext, fdo, interface};
 
 static WRITE_LOCK: OnceCell<Arc<Mutex<()>>> = OnceCell::const_new();
 
-async fn get_write_lock_guard() -> MutexGuard<'static, ()> {
+pub(crate) async fn get_write_lock_guard() -> MutexGuard<'static, ()> {
     let lock = WRITE_LOCK
         .get_or_init(|| async { Arc::new(Mutex::new(())) })
         .await;
@@ -53,6 +71,100 @@ fn make_firmware_pair(
     }
 }
 
+/// Materializes `bytes` to a temporary firmware file, loads them onto
+/// `device_handle` via the FPGA manager, and removes the temporary file.
+fn load_bytes_to_device(
+    platform_string: &str,
+    device_handle: &str,
+    bytes: &[u8],
+) -> Result<(), FpgadError> {
+    let platform = platform_from_compat_or_device(platform_string, device_handle)?;
+    let firmware_source_dir =
+        system_config::firmware_source_dir().unwrap_or_else(|_| crate::config::FIRMWARE_SOURCE_DIR.to_string());
+    let filename = format!("fpgad-inline-{device_handle}.bin");
+    let temp_abs = PathBuf::from(&firmware_source_dir).join(&filename);
+    fs_write_bytes(&temp_abs, true, bytes)?;
+    let result = platform
+        .fpga(device_handle)?
+        .load_firmware(Path::new(&filename));
+    if let Err(e) = remove_file(&temp_abs) {
+        trace!("could not remove temporary bitstream {temp_abs:?}: {e}");
+    }
+    result
+}
+
+/// Resolves the FPGA manager an overlay's fpga-region targets. The overlay's
+/// `fpga-mgr` phandle is mapped to the node that declares it and matched against
+/// the managers under [`FPGA_MANAGERS_DIR`]; when the phandle is absent or cannot
+/// be mapped, the caller-supplied `device_handle` is used.
+fn resolve_overlay_manager(overlay_blob: &[u8], device_handle: &str) -> Result<String, FpgadError> {
+    if let Some(phandle) = fdt::fpga_mgr_phandle_anywhere(overlay_blob)? {
+        if let Some(node_name) = fdt::node_name_for_phandle(overlay_blob, phandle)? {
+            // Node names carry a unit address (e.g. "fpga-mgr@f8007000"); match
+            // against the manager handles by the label before the '@', and cross
+            // check the driver-reported name under /sys/class/fpga_manager/{dev}/name.
+            let label = node_name.split('@').next().unwrap_or(&node_name);
+            for manager in crate::platforms::platform::list_fpga_managers()? {
+                if manager.contains(label) || node_name.contains(&manager) {
+                    return Ok(manager);
+                }
+                let name_path = Path::new(FPGA_MANAGERS_DIR).join(&manager).join("name");
+                if let Ok(name) = crate::system_io::fs_read(&name_path) {
+                    if name.trim().contains(label) {
+                        return Ok(manager);
+                    }
+                }
+            }
+        }
+    }
+    Ok(device_handle.to_string())
+}
+
+/// Returns the on-disk path backing a streaming session, validating the id so
+/// it cannot escape the firmware directory.
+fn stream_temp_path(stream_id: &str) -> Result<PathBuf, FpgadError> {
+    if stream_id.is_empty() || !stream_id.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_') {
+        return Err(FpgadError::Argument(format!(
+            "invalid stream id '{stream_id}'"
+        )));
+    }
+    let firmware_source_dir =
+        system_config::firmware_source_dir().unwrap_or_else(|_| crate::config::FIRMWARE_SOURCE_DIR.to_string());
+    Ok(PathBuf::from(firmware_source_dir).join(format!("fpgad-stream-{stream_id}.bin")))
+}
+
+/// Reads a loading device's current phase and a coarse 0..=100 percent from its
+/// sysfs `state` node, for emitting progress. The percent is derived from the
+/// state alone; `remaining_size` is not read. Falls back to the `writing` phase
+/// when the node is unreadable.
+fn read_load_progress(device_handle: &str) -> (String, u8) {
+    use crate::platforms::platform::FpgaState;
+    let base = Path::new(FPGA_MANAGERS_DIR).join(device_handle);
+    let state = crate::system_io::fs_read(&base.join("state"))
+        .map(|s| FpgaState::parse(&s))
+        .unwrap_or(FpgaState::Unknown);
+    let phase = match state {
+        FpgaState::FirmwareRequest => "requesting",
+        FpgaState::WriteInit | FpgaState::Write => "writing",
+        FpgaState::WriteComplete => "programming",
+        FpgaState::Operating => "operating",
+        _ => "writing",
+    };
+    (phase.to_string(), state.progress_percent())
+}
+
+/// Extracts a device error code from a failed load for the [`load_finished`]
+/// signal, recovering a driver errno from an I/O error where present and
+/// defaulting to `-1` for non-OS failures.
+fn load_error_code(err: &FpgadError) -> i32 {
+    match err {
+        FpgadError::IOWrite { e, .. } | FpgadError::IORead { e, .. } => {
+            e.raw_os_error().unwrap_or(-1)
+        }
+        _ => -1,
+    }
+}
+
 pub struct ControlInterface {}
 #[interface(name = "com.canonical.fpgad.control")]
 impl ControlInterface {
@@ -64,13 +176,31 @@ impl ControlInterface {
     ) -> Result<String, fdo::Error> {
         trace!("set_fpga_flags called with name: {device_handle} and flags: {flags}");
         validate_device_handle(device_handle)?;
+        let flags = ReconfigFlags::validate(flags)?;
         let platform = platform_from_compat_or_device(platform_string, device_handle)?;
         platform.fpga(device_handle)?.set_flags(flags)?;
         Ok(format!("Flags set to {flags} for {device_handle}"))
     }
 
+    /// As [`set_fpga_flags`], but takes a comma-separated list of symbolic flag
+    /// names (e.g. `"partial,encrypted"`) instead of a raw bitmask.
+    async fn set_fpga_flags_symbolic(
+        &self,
+        platform_string: &str,
+        device_handle: &str,
+        flags: &str,
+    ) -> Result<String, fdo::Error> {
+        trace!("set_fpga_flags_symbolic called with name: {device_handle} and flags: {flags}");
+        validate_device_handle(device_handle)?;
+        let parsed = ReconfigFlags::parse(flags)?;
+        let platform = platform_from_compat_or_device(platform_string, device_handle)?;
+        platform.fpga(device_handle)?.set_flags(parsed)?;
+        Ok(format!("Flags set to {parsed} ({flags}) for {device_handle}"))
+    }
+
     async fn write_bitstream_direct(
         &self,
+        #[zbus(signal_context)] ctxt: SignalContext<'_>,
         platform_string: &str,
         device_handle: &str,
         bitstream_path_str: &str,
@@ -87,20 +217,303 @@ impl ControlInterface {
             )));
         }
         let platform = platform_from_compat_or_device(platform_string, device_handle)?;
-        let (prefix, suffix) = make_firmware_pair(path, Path::new(firmware_lookup_path))?;
+        let mut out_of_tree_copy: Option<PathBuf> = None;
+        let (prefix, suffix) = match make_firmware_pair(path, Path::new(firmware_lookup_path)) {
+            Ok(pair) => pair,
+            Err(_) => {
+                // The image lives outside the firmware search path, so stream it
+                // into the configured firmware dir in fixed-size chunks — a
+                // multi-megabyte bitstream never fully materializes in memory —
+                // and then load it by the copied name. The copy is temporary and
+                // removed once the load completes, mirroring the other inline/
+                // buffer load paths in this file.
+                let firmware_source_dir = system_config::firmware_source_dir()
+                    .unwrap_or_else(|_| crate::config::FIRMWARE_SOURCE_DIR.to_string());
+                let filename = path.file_name().ok_or_else(|| {
+                    FpgadError::Argument(format!("{bitstream_path_str} has no file name"))
+                })?;
+                let dst = PathBuf::from(&firmware_source_dir).join(filename);
+                crate::system_io::fs_copy_to(path, &dst)?;
+                out_of_tree_copy = Some(dst);
+                (PathBuf::from(&firmware_source_dir), PathBuf::from(filename))
+            }
+        };
 
-        let _guard = get_write_lock_guard();
+        let _guard = get_write_lock_guard().await;
         trace!("Got write lock.");
         write_firmware_source_dir(&prefix.to_string_lossy())?;
-        platform.fpga(device_handle)?.load_firmware(&suffix)?;
+        // Expand a compressed image (e.g. `.bit.gz`) into the firmware search dir
+        // before handing it to the manager, which only accepts raw bitstreams.
+        // The guard keeps the temporary file alive until after the load and
+        // removes it on drop, still under the write lock.
+        let _decompressed = crate::system_io::materialize_if_compressed(&prefix.join(&suffix))?;
+        let load_suffix = match &_decompressed {
+            Some(d) => d
+                .path()
+                .strip_prefix(&prefix)
+                .unwrap_or(d.path())
+                .to_path_buf(),
+            None => suffix.clone(),
+        };
+        // Isolate the fabric across the load: any bridges associated with this
+        // device are disabled before the image is programmed and re-enabled
+        // afterwards, even on the error path, so a partially-configured region
+        // never drives the bus (mirrors the barebox fpga-region sequence).
+        let bridges = crate::platforms::platform::device_bridges(device_handle);
+        let fpga = platform.fpga(device_handle)?;
+        universal_bridge::disable_bridges(&bridges)?;
+        let result = fpga.load_firmware(&load_suffix);
+        if let Err(e) = universal_bridge::enable_bridges(&bridges) {
+            trace!("failed to re-enable bridges after load: {e}");
+        }
+        if let Some(dst) = &out_of_tree_copy {
+            if let Err(e) = remove_file(dst) {
+                trace!("could not remove temporary out-of-tree copy {dst:?}: {e}");
+            }
+        }
+        result?;
+        xclbin::clear_design_info(device_handle);
+        let _ = Self::bitstream_loaded(&ctxt, platform_string, device_handle, bitstream_path_str)
+            .await;
         Ok(format!(
             "{bitstream_path_str} loaded to {device_handle} using firmware lookup path: '\
          {firmware_lookup_path}'"
         ))
     }
 
+    /// Loads a bitstream only after it passes integrity verification. The image
+    /// must resolve under `firmware_lookup_path`; unlike
+    /// [`write_bitstream_direct`], an image outside it is rejected rather than
+    /// copied in, and compressed images are not expanded. Its SHA-256 is checked against
+    /// `expected_digest` (lowercase hex; empty to skip) and the detached
+    /// `signature` is verified against the trusted keys (empty to skip) before a
+    /// single byte reaches the FPGA manager. `policy` selects `enforce` (a failed
+    /// check aborts with [`FpgadError::Verification`], mapped to
+    /// `fdo::Error::AccessDenied`) or `warn-only` (failures are logged and the
+    /// load proceeds) so operators can roll verification out gradually.
+    async fn write_bitstream_verified(
+        &self,
+        platform_string: &str,
+        device_handle: &str,
+        bitstream_path_str: &str,
+        firmware_lookup_path: &str,
+        expected_digest: &str,
+        signature: Vec<u8>,
+        policy: &str,
+    ) -> Result<String, fdo::Error> {
+        trace!(
+            "write_bitstream_verified called for {device_handle} path {bitstream_path_str} policy '{policy}'"
+        );
+        validate_device_handle(device_handle)?;
+        let path = Path::new(bitstream_path_str);
+        if !path.exists() || path.is_dir() {
+            return Err(fdo::Error::InvalidArgs(format!(
+                "{bitstream_path_str} is not a valid path to a bitstream file."
+            )));
+        }
+        let policy = bitstream_verify::VerificationPolicy::parse(policy)?;
+        let platform = platform_from_compat_or_device(platform_string, device_handle)?;
+        let (prefix, suffix) = make_firmware_pair(path, Path::new(firmware_lookup_path))?;
+        let resolved = prefix.join(&suffix);
+
+        let _guard = get_write_lock_guard().await;
+        trace!("Got write lock.");
+        // Verify first so a rejected image never touches FPGA_MANAGERS_DIR.
+        bitstream_verify::verify_bitstream(
+            &resolved,
+            Some(expected_digest),
+            Some(&signature),
+            policy,
+        )?;
+        write_firmware_source_dir(&prefix.to_string_lossy())?;
+        platform.fpga(device_handle)?.load_firmware(&suffix)?;
+        xclbin::clear_design_info(device_handle);
+        Ok(format!(
+            "{bitstream_path_str} verified and loaded to {device_handle}"
+        ))
+    }
+
+    /// Loads a bitstream handed over as a file descriptor (a memfd or pipe)
+    /// rather than copied inline or pre-staged by the caller. The daemon
+    /// reads the descriptor to EOF, transparently decompresses the image, and
+    /// hands it to [`Fpga::load_firmware_bytes`], which writes it as a temporary
+    /// file named `name` in the firmware source dir, loads it and removes it
+    /// again. Producers that synthesize or fetch images at runtime can thus
+    /// program without placing files in the firmware search path themselves.
+    async fn write_bitstream_fd(
+        &self,
+        platform_string: &str,
+        device_handle: &str,
+        image: zbus::zvariant::OwnedFd,
+        name: &str,
+    ) -> Result<String, fdo::Error> {
+        use std::io::Read;
+        trace!("write_bitstream_fd called with name: {device_handle} image '{name}'");
+        validate_device_handle(device_handle)?;
+        let platform = platform_from_compat_or_device(platform_string, device_handle)?;
+        let mut file = std::fs::File::from(std::os::fd::OwnedFd::from(image));
+        let mut bytes = Vec::new();
+        file.read_to_end(&mut bytes)
+            .map_err(|e| FpgadError::IORead {
+                file: PathBuf::from("<fd>"),
+                e,
+            })?;
+        let bytes = maybe_decompress(bytes)?;
+        let name = if name.is_empty() {
+            format!("fpgad-fd-{device_handle}.bin")
+        } else {
+            name.to_string()
+        };
+
+        let _guard = get_write_lock_guard().await;
+        trace!("Got write lock.");
+        platform
+            .fpga(device_handle)?
+            .load_firmware_bytes(&name, &bytes)?;
+        xclbin::clear_design_info(device_handle);
+        Ok(format!(
+            "{} bytes from fd loaded to {device_handle} as '{name}'",
+            bytes.len()
+        ))
+    }
+
+    /// Associates a set of bridges with `device_handle` so the load flow knows
+    /// which bridges to decouple around a reconfiguration of that device.
+    async fn set_device_bridges(
+        &self,
+        device_handle: &str,
+        bridges: Vec<String>,
+    ) -> Result<String, fdo::Error> {
+        trace!(
+            "set_device_bridges called for {device_handle} with {} bridge(s)",
+            bridges.len()
+        );
+        validate_device_handle(device_handle)?;
+        let count = bridges.len();
+        crate::platforms::platform::configure_device_bridges(device_handle, bridges);
+        Ok(format!("{count} bridge(s) associated with {device_handle}"))
+    }
+
+    /// Loads a bitstream while isolating the fabric: the named bridges are
+    /// disabled, the image is programmed, and the bridges are re-enabled on
+    /// success. If programming fails the bridges are still brought back up so the
+    /// fabric is never left stranded. Passing an empty `bridges` list gates the
+    /// bridges configured for `device_handle` with [`set_device_bridges`]; with
+    /// none configured either, it degrades to a plain load.
+    async fn write_bitstream_bracketed(
+        &self,
+        platform_string: &str,
+        device_handle: &str,
+        firmware_rel: &str,
+        flags: &str,
+        bridges: Vec<String>,
+    ) -> Result<String, fdo::Error> {
+        trace!(
+            "write_bitstream_bracketed called for {device_handle} gating {} bridge(s)",
+            bridges.len()
+        );
+        validate_device_handle(device_handle)?;
+        let flags = ReconfigFlags::parse(flags)?;
+        let image = FpgaImageInfo::new(firmware_rel, flags);
+        let platform = platform_from_compat_or_device(platform_string, device_handle)?;
+        // Fall back to the bridges configured against this device when the caller
+        // passes an empty list, so per-device associations are honored.
+        let bridges = if bridges.is_empty() {
+            crate::platforms::platform::device_bridges(device_handle)
+        } else {
+            bridges
+        };
+        let _guard = get_write_lock_guard().await;
+        trace!("Got write lock.");
+        let fpga = platform.fpga(device_handle)?;
+        universal_bridge::disable_bridges(&bridges)?;
+        let result = fpga.program(&image);
+        if let Err(e) = universal_bridge::enable_bridges(&bridges) {
+            trace!("failed to re-enable bridges after load: {e}");
+        }
+        result?;
+        Ok(format!(
+            "{firmware_rel} programmed to {device_handle} with {} bridge(s) gated",
+            bridges.len()
+        ))
+    }
+
+    /// Loads a fwupd/LVFS-style firmware package: the CAB at `package_path` is
+    /// opened, its payload SHA-256 is verified against the declared digest and
+    /// its target constraints are checked against `device_handle`/`platform_string`
+    /// before anything is written, and only then is the payload streamed into the
+    /// normal programming path. Returns the verified `id version` followed by the
+    /// device it was loaded to.
+    async fn load_firmware_package(
+        &self,
+        platform_string: &str,
+        device_handle: &str,
+        package_path: &str,
+        flags: &str,
+    ) -> Result<String, fdo::Error> {
+        trace!("load_firmware_package called for {device_handle} package {package_path}");
+        validate_device_handle(device_handle)?;
+        let verified =
+            firmware_package::verify_package(Path::new(package_path), device_handle, platform_string)?;
+        let flags = ReconfigFlags::parse(flags)?;
+        let platform = platform_from_compat_or_device(platform_string, device_handle)?;
+        let id_version = verified.id_version();
+        let image = FpgaImageInfo::from_buffer(verified.payload, flags);
+        let _guard = get_write_lock_guard().await;
+        trace!("Got write lock.");
+        platform.fpga(device_handle)?.program(&image)?;
+        xclbin::clear_design_info(device_handle);
+        Ok(format!("{id_version} loaded to {device_handle}"))
+    }
+
+    /// Programs several FPGAs from a single concatenated image set. The buffer
+    /// holds one length-prefixed segment per device (see
+    /// [`chained_load::split_segments`]); segment N is loaded onto
+    /// `device_handles[N]`. Loads are performed in order and stop at the first
+    /// failure.
+    async fn write_chained_bitstreams(
+        &self,
+        platform_string: &str,
+        device_handles: Vec<String>,
+        concatenated: Vec<u8>,
+    ) -> Result<String, fdo::Error> {
+        trace!(
+            "write_chained_bitstreams called for {} device(s) and {} bytes",
+            device_handles.len(),
+            concatenated.len()
+        );
+        let segments = chained_load::split_segments(&maybe_decompress(concatenated)?)?;
+        if segments.len() != device_handles.len() {
+            return Err(fdo::Error::from(FpgadError::Argument(format!(
+                "image set has {} segment(s) but {} device(s) were given",
+                segments.len(),
+                device_handles.len()
+            ))));
+        }
+        let _guard = get_write_lock_guard().await;
+        trace!("Got write lock.");
+        for (device_handle, segment) in device_handles.iter().zip(&segments) {
+            validate_device_handle(device_handle)?;
+            load_bytes_to_device(platform_string, device_handle, segment)?;
+        }
+        Ok(format!("{} bitstreams loaded in chain", segments.len()))
+    }
+
+    /// Applies a device-tree overlay following the kernel/barebox `fpga-region`
+    /// bring-up sequence end to end. The compiled overlay is parsed for its
+    /// fpga-region metadata: the `fpga-mgr` phandle naming the manager, the
+    /// `firmware-name` of the bitstream to program, and the reconfiguration
+    /// flags derived from the overlay (partial-config and friends). When the
+    /// overlay names a firmware, the responsible manager is resolved, the
+    /// derived flags are set, the manager's bridges are gated, the named
+    /// bitstream is programmed, and only then are the nodes grafted; the bridges
+    /// are restored and the previous flags rolled back if any step fails.
+    /// Overlays with no `firmware-name` apply unchanged, reproducing the plain
+    /// `of_firmware_load_overlay()` path. This single call subsumes the former
+    /// managed / with-flags / autoprogram / autoselect / bracketed variants.
     async fn apply_overlay(
         &self,
+        #[zbus(signal_context)] ctxt: SignalContext<'_>,
         platform_compat_str: &str,
         overlay_handle: &str,
         overlay_source_path: &str,
@@ -117,19 +530,177 @@ impl ControlInterface {
             Path::new(overlay_source_path),
             Path::new(firmware_lookup_path),
         )?;
+        let overlay_blob = std::fs::read(prefix.join(&suffix)).map_err(|e| {
+            FpgadError::OverlayStatus(format!("could not read overlay {overlay_source_path}: {e}"))
+        })?;
+        let firmware_name = overlay_handler.overlay_firmware_name(&suffix)?;
 
-        let _guard = get_write_lock_guard();
+        let _guard = get_write_lock_guard().await;
         trace!("Got write lock.");
+        let _ =
+            Self::overlay_applying(&ctxt, platform_compat_str, overlay_handle, overlay_source_path)
+                .await;
         write_firmware_source_dir(&prefix.to_string_lossy())?;
-        overlay_handler.apply_overlay(&suffix)?;
-        Ok(format!(
-            "{overlay_source_path} loaded via {overlay_fs_path:?} using firmware lookup path: '\
-         {firmware_lookup_path}'"
-        ))
+
+        // When the overlay declares a `firmware-name`, reproduce the full
+        // fpga-region sequence: resolve the manager from the overlay's
+        // `fpga-mgr` phandle, honor the flags the overlay requires, gate the
+        // manager's bridges, and program the named bitstream before the nodes
+        // are grafted. Any failure restores the bridges and flags so a failed
+        // apply never leaves the fabric or manager half-configured. Overlays
+        // with no firmware-name skip straight to the plain graft.
+        let rollback = if let Some(firmware_name) = &firmware_name {
+            let manager = resolve_overlay_manager(&overlay_blob, "")?;
+            if manager.is_empty() {
+                return Err(fdo::Error::from(FpgadError::OverlayStatus(format!(
+                    "overlay '{overlay_handle}' names firmware '{firmware_name}' but its \
+                     fpga-mgr phandle could not be resolved to a manager"
+                ))));
+            }
+            // Strip fpgad-internal sentinel bits before anything reaches
+            // `set_flags`; only genuine kernel flags may be programmed.
+            let flags = u32::try_from(strip_internal_flags(
+                overlay_handler.required_flags(&overlay_blob)?,
+            ))
+            .map_err(|_| FpgadError::Argument("overlay required_flags out of range".into()))?;
+            let fpga = platform.fpga(&manager)?;
+            let previous = fpga.flags()?;
+            let bridges = crate::platforms::platform::device_bridges(&manager);
+            trace!(
+                "overlay declares firmware-name '{firmware_name}', programming onto {manager} with flags {flags:#x}"
+            );
+            fpga.set_flags(flags)?;
+            if let Err(e) = universal_bridge::disable_bridges(&bridges) {
+                if let Err(restore) = fpga.set_flags(previous) {
+                    trace!("failed to restore flags {previous:#x} after overlay error: {restore}");
+                }
+                return Err(fdo::Error::from(e));
+            }
+            let image = FpgaImageInfo::new(firmware_name, flags);
+            let result = fpga.program(&image);
+            if let Err(e) = universal_bridge::enable_bridges(&bridges) {
+                trace!("failed to re-enable bridges after overlay program: {e}");
+            }
+            if let Err(e) = result {
+                if let Err(restore) = fpga.set_flags(previous) {
+                    trace!("failed to restore flags {previous:#x} after overlay error: {restore}");
+                }
+                return Err(fdo::Error::from(e));
+            }
+            Some((fpga, previous))
+        } else {
+            None
+        };
+        if let Err(e) = overlay_handler.apply_overlay(&suffix) {
+            if let Some((fpga, previous)) = rollback {
+                if let Err(restore) = fpga.set_flags(previous) {
+                    trace!("failed to restore flags {previous:#x} after overlay error: {restore}");
+                }
+            }
+            return Err(fdo::Error::from(e));
+        }
+        let status = match &firmware_name {
+            Some(name) => format!(
+                "{overlay_source_path} loaded via {overlay_fs_path:?} after programming firmware \
+                 '{name}' (firmware lookup path: '{firmware_lookup_path}')"
+            ),
+            None => format!(
+                "{overlay_source_path} loaded via {overlay_fs_path:?} using firmware lookup path: \
+                 '{firmware_lookup_path}'"
+            ),
+        };
+        let _ = Self::overlay_applied(
+            &ctxt,
+            platform_compat_str,
+            overlay_handle,
+            overlay_source_path,
+            &status,
+        )
+        .await;
+        Ok(status)
+    }
+
+    /// Applies a set of stacked overlays as one all-or-nothing transaction.
+    /// Each `(overlay_handle, overlay_source_path)` pair is created and written
+    /// in order; if any apply fails, every overlay already applied is removed in
+    /// reverse order so the configfs tree is restored. The `firmware-name`
+    /// references declared across the whole set are deduplicated and each named
+    /// bitstream is loaded onto `device_handle` exactly once after the overlays
+    /// commit. Returns per-overlay status followed by the firmware that was
+    /// loaded.
+    async fn apply_overlays(
+        &self,
+        platform_string: &str,
+        device_handle: &str,
+        overlays: Vec<(String, String)>,
+        firmware_lookup_path: &str,
+    ) -> Result<String, fdo::Error> {
+        trace!(
+            "apply_overlays called for {device_handle} with {} overlay(s)",
+            overlays.len()
+        );
+        validate_device_handle(device_handle)?;
+        let platform = platform_from_compat_or_device(platform_string, device_handle)?;
+
+        // Resolve each source path against the shared firmware lookup path up
+        // front so the transaction works in firmware-relative terms.
+        let mut prefix: Option<PathBuf> = None;
+        let mut resolved: Vec<(String, PathBuf)> = Vec::with_capacity(overlays.len());
+        for (overlay_handle, overlay_source_path) in &overlays {
+            let (this_prefix, suffix) = make_firmware_pair(
+                Path::new(overlay_source_path),
+                Path::new(firmware_lookup_path),
+            )?;
+            prefix.get_or_insert(this_prefix);
+            resolved.push((overlay_handle.clone(), suffix));
+        }
+
+        let _guard = get_write_lock_guard().await;
+        trace!("Got write lock.");
+        if let Some(prefix) = &prefix {
+            write_firmware_source_dir(&prefix.to_string_lossy())?;
+        }
+
+        let specs: Vec<overlay_transaction::OverlaySpec> = resolved
+            .iter()
+            .map(|(handle, suffix)| overlay_transaction::OverlaySpec {
+                overlay_handle: handle.as_str(),
+                source_path_rel: suffix.as_path(),
+            })
+            .collect();
+
+        let mut transaction = overlay_transaction::OverlayTransaction::new();
+        let report = transaction.apply_all(&specs)?;
+        transaction.commit();
+
+        // Load each distinct firmware referenced across the set exactly once.
+        let fpga = platform.fpga(device_handle)?;
+        for firmware in &report.firmwares {
+            trace!("loading deduplicated firmware '{firmware}' onto {device_handle}");
+            fpga.load_firmware(Path::new(firmware))?;
+        }
+
+        let mut summary = String::new();
+        for outcome in &report.outcomes {
+            summary.push_str(&format!(
+                "{}: {}\n",
+                outcome.overlay_handle, outcome.status
+            ));
+        }
+        if report.firmwares.is_empty() {
+            summary.push_str("no firmware-name references across the set");
+        } else {
+            summary.push_str(&format!(
+                "loaded firmware: {}",
+                report.firmwares.join(", ")
+            ));
+        }
+        Ok(summary)
     }
 
     async fn remove_overlay(
         &self,
+        #[zbus(signal_context)] ctxt: SignalContext<'_>,
         platform_compat_str: &str,
         overlay_handle: &str,
     ) -> Result<String, fdo::Error> {
@@ -140,12 +711,349 @@ impl ControlInterface {
         let platform = platform_for_known_platform(platform_compat_str)?;
         let overlay_handler = platform.overlay_handler(overlay_handle)?;
         let overlay_fs_path = overlay_handler.overlay_fs_path()?;
+        let _ = Self::overlay_removing(&ctxt, platform_compat_str, overlay_handle).await;
         overlay_handler.remove_overlay()?;
+        let _ = Self::overlay_removed(&ctxt, platform_compat_str, overlay_handle).await;
         Ok(format!(
             "{overlay_handle} removed by deleting {overlay_fs_path:?}"
         ))
     }
 
+    /// Re-applies the image last programmed onto `device_handle` this session,
+    /// reprogramming the fabric from the recorded [`FpgaImageInfo`] without the
+    /// caller re-specifying the original parameters. Intended for deterministic
+    /// recovery after a suspend/resume cycle or an unexpected fabric reset; the
+    /// daemon's resume hook calls the same path for every tracked device.
+    async fn reprogram_on_resume(&self, device_handle: &str) -> Result<String, fdo::Error> {
+        trace!("reprogram_on_resume called for {device_handle}");
+        validate_device_handle(device_handle)?;
+        let _guard = get_write_lock_guard().await;
+        trace!("Got write lock.");
+        crate::platforms::platform::reprogram_device_on_resume(device_handle)?;
+        Ok(format!("{device_handle} reprogrammed from its last recorded image"))
+    }
+
+    /// Removes a programmed bitstream by tearing down the region that carried
+    /// it: the bridges are disabled, the overlay is removed (unloading the
+    /// bitstream), and the bridges are re-enabled.
+    async fn remove_bitstream(
+        &self,
+        bridges: Vec<String>,
+        overlay_handle: &str,
+    ) -> Result<String, fdo::Error> {
+        trace!("remove_bitstream called for overlay {overlay_handle}");
+        let bridge_refs: Vec<&str> = bridges.iter().map(String::as_str).collect();
+        let _guard = get_write_lock_guard().await;
+        fpga_region::teardown_region(&bridge_refs, overlay_handle)?;
+        Ok(format!("region for overlay {overlay_handle} torn down"))
+    }
+
+    /// Appends a chunk of a bitstream to an on-disk streaming session named
+    /// `stream_id`. Large images that exceed the D-Bus message size can be sent
+    /// as a sequence of `append_bitstream_chunk` calls followed by
+    /// `commit_bitstream_stream`. The first chunk (re)starts the session.
+    async fn append_bitstream_chunk(
+        &self,
+        stream_id: &str,
+        chunk: Vec<u8>,
+        first: bool,
+    ) -> Result<String, fdo::Error> {
+        trace!(
+            "append_bitstream_chunk called for '{stream_id}' ({} bytes, first={first})",
+            chunk.len()
+        );
+        let temp_abs = stream_temp_path(stream_id)?;
+        if first {
+            let _ = remove_file(&temp_abs);
+        }
+        let _guard = get_write_lock_guard().await;
+        fs_append_bytes(&temp_abs, &chunk)?;
+        Ok(format!("{} bytes appended to stream '{stream_id}'", chunk.len()))
+    }
+
+    /// Loads a bitstream accumulated via `append_bitstream_chunk` onto a device,
+    /// then removes the streaming file.
+    async fn commit_bitstream_stream(
+        &self,
+        platform_string: &str,
+        device_handle: &str,
+        stream_id: &str,
+        flags: u32,
+    ) -> Result<String, fdo::Error> {
+        trace!("commit_bitstream_stream called for '{stream_id}' -> {device_handle}");
+        validate_device_handle(device_handle)?;
+        let temp_abs = stream_temp_path(stream_id)?;
+        if !temp_abs.exists() {
+            return Err(fdo::Error::from(FpgadError::Argument(format!(
+                "no streaming session '{stream_id}' to commit"
+            ))));
+        }
+        let firmware_source_dir = system_config::firmware_source_dir()
+            .unwrap_or_else(|_| crate::config::FIRMWARE_SOURCE_DIR.to_string());
+        let rel = temp_abs
+            .strip_prefix(&firmware_source_dir)
+            .unwrap_or(&temp_abs)
+            .to_path_buf();
+        let platform = platform_from_compat_or_device(platform_string, device_handle)?;
+        let _guard = get_write_lock_guard().await;
+        let image = FpgaImageInfo::new(rel, flags);
+        let result = platform.fpga(device_handle)?.program(&image);
+        if let Err(e) = remove_file(&temp_abs) {
+            trace!("could not remove streaming file {temp_abs:?}: {e}");
+        }
+        result?;
+        Ok(format!("stream '{stream_id}' loaded to {device_handle}"))
+    }
+
+    /// Programs an FPGA region atomically: disables the named bridges, sets the
+    /// flags, applies the overlay, and re-enables the bridges, rolling back the
+    /// overlay if anything fails (see [`fpga_region::program_region`]).
+    async fn program_region(
+        &self,
+        platform_string: &str,
+        device_handle: &str,
+        bridges: Vec<String>,
+        flags: u32,
+        overlay_handle: &str,
+        overlay_source_rel: &str,
+    ) -> Result<String, fdo::Error> {
+        trace!("program_region called for {device_handle} overlay {overlay_handle}");
+        validate_device_handle(device_handle)?;
+        let platform = platform_from_compat_or_device(platform_string, device_handle)?;
+        let bridge_refs: Vec<&str> = bridges.iter().map(String::as_str).collect();
+        let request = RegionRequest {
+            bridges: &bridge_refs,
+            flags,
+            overlay_handle,
+            overlay_source_rel: Path::new(overlay_source_rel),
+        };
+        let _guard = get_write_lock_guard().await;
+        fpga_region::program_region(platform.fpga(device_handle)?, &request)?;
+        Ok(format!(
+            "region on {device_handle} programmed with overlay {overlay_handle}"
+        ))
+    }
+
+    /// Single programming entry point that auto-selects the image source,
+    /// mirroring the kernel's consolidation of the buffer/firmware/scatter-gather
+    /// loaders into one `fpga_mgr_load` driven by an image-info struct. `source_kind`
+    /// selects how `source` is interpreted:
+    ///
+    /// * `"firmware"` — `source` is a path relative to the firmware source dir;
+    /// * `"path"` — `source` is an absolute path resolved under the firmware dir;
+    /// * `"bytes"` — `source` is ignored and `bytes` carries the image inline;
+    /// * `"xclbin"` — `source` is a path to a Xilinx `xclbin` container; its
+    ///   embedded `BITSTREAM` section is extracted and programmed and the design
+    ///   metadata is recorded for later query.
+    ///
+    /// `flags` is the reconfiguration-flags bitfield, making partial-reconfig a
+    /// first-class field rather than an out-of-band [`set_fpga_flags`] call.
+    async fn program_fpga(
+        &self,
+        platform_string: &str,
+        device_handle: &str,
+        source_kind: &str,
+        source: &str,
+        bytes: Vec<u8>,
+        flags: u32,
+    ) -> Result<String, fdo::Error> {
+        trace!(
+            "program_fpga called for {device_handle} source_kind '{source_kind}' flags {flags:#x}"
+        );
+        validate_device_handle(device_handle)?;
+        let flags = ReconfigFlags::validate(flags)?;
+        let platform = platform_from_compat_or_device(platform_string, device_handle)?;
+        let _guard = get_write_lock_guard().await;
+        trace!("Got write lock.");
+        match source_kind {
+            "bytes" => {
+                let bytes = maybe_decompress(bytes)?;
+                let bytes_len = bytes.len();
+                let image = FpgaImageInfo::from_buffer(bytes, flags);
+                platform.fpga(device_handle)?.program(&image)?;
+                xclbin::clear_design_info(device_handle);
+                Ok(format!("{bytes_len} inline bytes programmed to {device_handle}"))
+            }
+            "firmware" | "path" => {
+                let suffix = if source_kind == "path" {
+                    let path = Path::new(source);
+                    if !path.exists() || path.is_dir() {
+                        return Err(fdo::Error::InvalidArgs(format!(
+                            "{source} is not a valid path to a bitstream file."
+                        )));
+                    }
+                    let firmware_source_dir = system_config::firmware_source_dir()
+                        .unwrap_or_else(|_| crate::config::FIRMWARE_SOURCE_DIR.to_string());
+                    let (prefix, suffix) = make_firmware_pair(path, Path::new(&firmware_source_dir))?;
+                    write_firmware_source_dir(&prefix.to_string_lossy())?;
+                    suffix
+                } else {
+                    PathBuf::from(source)
+                };
+                let image = FpgaImageInfo::new(suffix, flags);
+                platform.fpga(device_handle)?.program(&image)?;
+                xclbin::clear_design_info(device_handle);
+                Ok(format!("{source} programmed to {device_handle}"))
+            }
+            "xclbin" => {
+                let path = Path::new(source);
+                if !path.exists() || path.is_dir() {
+                    return Err(fdo::Error::InvalidArgs(format!(
+                        "{source} is not a valid path to an xclbin file."
+                    )));
+                }
+                let blob = std::fs::read(path).map_err(|e| {
+                    FpgadError::Argument(format!("could not read xclbin {source}: {e}"))
+                })?;
+                let bitstream = xclbin::extract_bitstream(&blob)?;
+                let design_info = xclbin::parse_design_info(&blob)?;
+                let bitstream_len = bitstream.len();
+                let image = FpgaImageInfo::from_buffer(bitstream, flags);
+                platform.fpga(device_handle)?.program(&image)?;
+                xclbin::record_design_info(device_handle, design_info);
+                Ok(format!(
+                    "{bitstream_len} byte BITSTREAM section of {source} loaded to {device_handle}"
+                ))
+            }
+            other => Err(fdo::Error::from(FpgadError::Argument(format!(
+                "unknown program_fpga source kind '{other}'"
+            )))),
+        }
+    }
+
+    /// Programs a device while emitting progress over D-Bus, for clients that
+    /// want to render a progress bar instead of blocking opaquely. A background
+    /// task polls the manager's `state` node (not `remaining_size`) and emits a
+    /// [`load_progress`] signal (phase string plus a 0..=100 percent) until the
+    /// load returns, after which a [`load_finished`] signal carries the outcome
+    /// and the device error code (0 on success). The load itself is the same
+    /// firmware-relative program path as [`program_fpga`].
+    async fn write_bitstream_monitored(
+        &self,
+        #[zbus(signal_context)] ctxt: SignalContext<'_>,
+        platform_string: &str,
+        device_handle: &str,
+        firmware_rel: &str,
+        flags: u32,
+    ) -> Result<String, fdo::Error> {
+        trace!("write_bitstream_monitored called for {device_handle} image {firmware_rel}");
+        validate_device_handle(device_handle)?;
+        let flags = ReconfigFlags::validate(flags)?;
+        let platform = platform_from_compat_or_device(platform_string, device_handle)?;
+        let image = FpgaImageInfo::new(firmware_rel, flags);
+
+        // Poll the manager state in the background and emit progress until the
+        // load completes; `stop` tears the poller down once programming returns.
+        let stop = Arc::new(std::sync::atomic::AtomicBool::new(false));
+        let poll_ctxt = ctxt.to_owned();
+        let poll_handle = device_handle.to_string();
+        let poll_stop = Arc::clone(&stop);
+        let poller = tokio::spawn(async move {
+            while !poll_stop.load(std::sync::atomic::Ordering::Relaxed) {
+                let (phase, percent) = read_load_progress(&poll_handle);
+                let _ = Self::load_progress(&poll_ctxt, &poll_handle, &phase, percent).await;
+                tokio::time::sleep(std::time::Duration::from_millis(100)).await;
+            }
+        });
+
+        let _guard = get_write_lock_guard().await;
+        trace!("Got write lock.");
+        let result = platform.fpga(device_handle)?.program(&image);
+        stop.store(true, std::sync::atomic::Ordering::Relaxed);
+        let _ = poller.await;
+        let error_code = match &result {
+            Ok(()) => 0,
+            Err(e) => load_error_code(e),
+        };
+        let _ = Self::load_finished(&ctxt, device_handle, result.is_ok(), error_code).await;
+        result?;
+        xclbin::clear_design_info(device_handle);
+        Ok(format!("{firmware_rel} programmed to {device_handle}"))
+    }
+
+    /// Enables or disables an FPGA bridge by handle, for callers that want to
+    /// gate the fabric around a reconfiguration themselves.
+    async fn set_bridge_enabled(
+        &self,
+        bridge_handle: &str,
+        enabled: bool,
+    ) -> Result<String, fdo::Error> {
+        trace!("set_bridge_enabled called with bridge: {bridge_handle} enabled: {enabled}");
+        UniversalBridge::new(bridge_handle).set_enabled(enabled)?;
+        Ok(format!(
+            "bridge {bridge_handle} {}",
+            if enabled { "enabled" } else { "disabled" }
+        ))
+    }
+
+    /// Performs a secure flash/BMC image update on a security manager, distinct
+    /// from live fabric reconfiguration: the image named by `filename` (resolved
+    /// through the kernel firmware lookup) is authenticated and persisted to
+    /// flash. Blocks until the multi-phase update completes (bounded by
+    /// [`flash_update::await_completion`]'s own timeout), returning the final
+    /// status or the driver's `update/error` on failure. The shared write lock
+    /// is only held while kicking the update off, not across the whole
+    /// multi-minute poll, so it never blocks unrelated reconfigurations for the
+    /// duration of a flash update.
+    async fn flash_update(
+        &self,
+        sec_mgr_handle: &str,
+        filename: &str,
+    ) -> Result<String, fdo::Error> {
+        trace!("flash_update called for {sec_mgr_handle} image {filename}");
+        {
+            let _guard = get_write_lock_guard().await;
+            trace!("Got write lock.");
+            flash_update::start_update(sec_mgr_handle, filename)?;
+        }
+        Ok(flash_update::await_completion(sec_mgr_handle, filename).await?)
+    }
+
+    /// Loads a named accelerator package into a reconfigurable slot via the
+    /// Xilinx DFX manager. Returns the dfx-mgr-client output, which reports the
+    /// slot the package was loaded into.
+    #[cfg(feature = "softeners")]
+    async fn dfx_load_accelerator(&self, accel_name: &str) -> Result<String, fdo::Error> {
+        trace!("dfx_load_accelerator called for '{accel_name}'");
+        Ok(xilinx_dfx_mgr::load(accel_name).map_err(FpgadError::Softener)?)
+    }
+
+    /// Removes the accelerator package occupying `slot`, freeing it for reuse.
+    #[cfg(feature = "softeners")]
+    async fn dfx_remove_accelerator(&self, slot: u32) -> Result<String, fdo::Error> {
+        trace!("dfx_remove_accelerator called for slot {slot}");
+        Ok(xilinx_dfx_mgr::remove(slot).map_err(FpgadError::Softener)?)
+    }
+
+    /// Routes the RM output stream of slot `from` into slot `to`.
+    #[cfg(feature = "softeners")]
+    async fn dfx_set_stream(&self, from: u32, to: u32) -> Result<String, fdo::Error> {
+        trace!("dfx_set_stream called {from} -> {to}");
+        Ok(xilinx_dfx_mgr::set_irbuf(from, to).map_err(FpgadError::Softener)?)
+    }
+
+    /// Allocates a DMA buffer of `size` bytes, returning the dfx-mgr-client
+    /// output carrying the buffer fd and physical address.
+    #[cfg(feature = "softeners")]
+    async fn dfx_alloc_buffer(&self, size: u64) -> Result<String, fdo::Error> {
+        trace!("dfx_alloc_buffer called for {size} bytes");
+        Ok(xilinx_dfx_mgr::alloc_buffer(size).map_err(FpgadError::Softener)?)
+    }
+
+    /// Frees the DMA buffer at physical address `pa`.
+    #[cfg(feature = "softeners")]
+    async fn dfx_free_buffer(&self, pa: u64) -> Result<String, fdo::Error> {
+        trace!("dfx_free_buffer called for pa {pa}");
+        Ok(xilinx_dfx_mgr::free_buffer(pa).map_err(FpgadError::Softener)?)
+    }
+
+    /// Sends the IP device file descriptors for `slot` over the dfx-mgr socket.
+    #[cfg(feature = "softeners")]
+    async fn dfx_get_fds(&self, slot: u32) -> Result<String, fdo::Error> {
+        trace!("dfx_get_fds called for slot {slot}");
+        Ok(xilinx_dfx_mgr::get_fds(slot).map_err(FpgadError::Softener)?)
+    }
+
     /// use to write to a device property from /sys/class/fpga_manager/<device>/** that does not have a specific interface
     async fn write_property(&self, property_path_str: &str, data: &str) -> Result<(), fdo::Error> {
         trace!(
@@ -159,4 +1067,72 @@ impl ControlInterface {
         }
         Ok(fs_write(property_path, false, data)?)
     }
+
+    /// Emitted just before an overlay is applied, so supervisors can quiesce
+    /// drivers that are about to be affected by the fabric change.
+    #[zbus(signal)]
+    async fn overlay_applying(
+        ctxt: &SignalContext<'_>,
+        platform_string: &str,
+        overlay_handle: &str,
+        overlay_source_path: &str,
+    ) -> zbus::Result<()>;
+
+    /// Emitted after an overlay has been applied, carrying the resulting status.
+    #[zbus(signal)]
+    async fn overlay_applied(
+        ctxt: &SignalContext<'_>,
+        platform_string: &str,
+        overlay_handle: &str,
+        overlay_source_path: &str,
+        status: &str,
+    ) -> zbus::Result<()>;
+
+    /// Emitted just before an overlay is removed, so supervisors can quiesce
+    /// drivers bound to the nodes that are about to disappear.
+    #[zbus(signal)]
+    async fn overlay_removing(
+        ctxt: &SignalContext<'_>,
+        platform_string: &str,
+        overlay_handle: &str,
+    ) -> zbus::Result<()>;
+
+    /// Emitted after an overlay has been removed.
+    #[zbus(signal)]
+    async fn overlay_removed(
+        ctxt: &SignalContext<'_>,
+        platform_string: &str,
+        overlay_handle: &str,
+    ) -> zbus::Result<()>;
+
+    /// Emitted after a bitstream has been programmed onto a device, so consumers
+    /// can rescan buses that the new image exposes.
+    #[zbus(signal)]
+    async fn bitstream_loaded(
+        ctxt: &SignalContext<'_>,
+        platform_string: &str,
+        device_handle: &str,
+        bitstream_path: &str,
+    ) -> zbus::Result<()>;
+
+    /// Emitted periodically while a monitored load is in flight, carrying the
+    /// device handle, the phase (`requesting`/`writing`/`programming`/...) and a
+    /// coarse 0..=100 percent, so clients can drive a progress bar.
+    #[zbus(signal)]
+    async fn load_progress(
+        ctxt: &SignalContext<'_>,
+        device_handle: &str,
+        phase: &str,
+        percent: u8,
+    ) -> zbus::Result<()>;
+
+    /// Emitted once a monitored load has finished, carrying whether it succeeded
+    /// and the device error code (0 on success).
+    #[zbus(signal)]
+    async fn load_finished(
+        ctxt: &SignalContext<'_>,
+        device_handle: &str,
+        success: bool,
+        error_code: i32,
+    ) -> zbus::Result<()>;
 }
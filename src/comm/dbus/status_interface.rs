@@ -10,14 +10,63 @@
 //
 // You should have received a copy of the GNU General Public License along with this program.  If not, see http://www.gnu.org/licenses/.
 
+use crate::platforms::platform::Bridge;
 use crate::platforms::platform::Fpga;
 use crate::platforms::platform::OverlayHandler;
 use crate::platforms::platform::Platform;
+use crate::platforms::platform::list_fpga_bridges;
 use crate::platforms::platform::new_platform;
+use crate::platforms::universal_components::fpga_region::{self, Region, UniversalRegion};
+use crate::platforms::universal_components::universal_bridge;
+use crate::platforms::universal_components::universal_overlay_handler;
+use crate::platforms::universal_components::xclbin;
+#[cfg(feature = "softeners")]
+use crate::softeners::xilinx_dfx_mgr;
 use crate::system_io::validate_device_handle;
 use log::trace;
 use zbus::{fdo, interface};
 
+/// Reads a device's `status` sysfs node and parses a trailing driver status
+/// code from it, returning 0 when the node is absent or carries no code. Used to
+/// populate the decoded `error` field of the structured inventory.
+fn read_driver_status_code(device_handle: &str) -> i32 {
+    let path = std::path::Path::new(crate::config::FPGA_MANAGERS_DIR)
+        .join(device_handle)
+        .join("status");
+    crate::system_io::fs_read(&path)
+        .ok()
+        .and_then(|s| {
+            s.split(|c: char| !c.is_ascii_digit() && c != '-')
+                .filter(|t| !t.is_empty())
+                .next_back()
+                .and_then(|t| t.parse::<i32>().ok())
+        })
+        .unwrap_or(0)
+}
+
+/// Escapes the characters that would otherwise break the hand-built JSON strings
+/// returned by the structured status methods. Besides `\` and `"`, every C0
+/// control character is escaped — the named short forms where JSON defines them
+/// and `\u00XX` for the rest — so compatible strings or decoded error text that
+/// carry embedded control bytes still produce valid JSON.
+fn json_escape(value: &str) -> String {
+    let mut out = String::with_capacity(value.len());
+    for c in value.chars() {
+        match c {
+            '\\' => out.push_str("\\\\"),
+            '"' => out.push_str("\\\""),
+            '\n' => out.push_str("\\n"),
+            '\t' => out.push_str("\\t"),
+            '\r' => out.push_str("\\r"),
+            '\u{0008}' => out.push_str("\\b"),
+            '\u{000c}' => out.push_str("\\f"),
+            c if (c as u32) < 0x20 => out.push_str(&format!("\\u{:04x}", c as u32)),
+            c => out.push(c),
+        }
+    }
+    out
+}
+
 pub struct StatusInterface {}
 
 #[interface(name = "com.canonical.fpgad.status")]
@@ -37,6 +86,254 @@ impl StatusInterface {
             .map(|flags| flags.to_string())?)
     }
 
+    /// Lists the FPGA bridges present on the system, one per line.
+    async fn get_fpga_bridges(&self) -> Result<String, fdo::Error> {
+        trace!("get_fpga_bridges called");
+        Ok(list_fpga_bridges()?.join("\n"))
+    }
+
+    /// Lists every FPGA bridge together with its current state, one
+    /// `<handle> enabled|disabled` pair per line.
+    async fn get_bridge_states(&self) -> Result<String, fdo::Error> {
+        trace!("get_bridge_states called");
+        let handles = list_fpga_bridges()?;
+        let states = universal_bridge::bridge_states(&handles)?;
+        Ok(states
+            .into_iter()
+            .map(|(h, enabled)| format!("{h} {}", if enabled { "enabled" } else { "disabled" }))
+            .collect::<Vec<_>>()
+            .join("\n"))
+    }
+
+    /// Returns the current state of a single FPGA bridge as `enabled` or
+    /// `disabled`.
+    async fn get_bridge_state(&self, bridge_handle: &str) -> Result<String, fdo::Error> {
+        trace!("get_bridge_state called for {bridge_handle}");
+        let enabled = universal_bridge::UniversalBridge::new(bridge_handle).is_enabled()?;
+        Ok(if enabled { "enabled" } else { "disabled" }.to_string())
+    }
+
+    /// Reports the bridges a given overlay isolated at apply time together with
+    /// their current enable state, one `<handle> enabled|disabled` pair per line.
+    /// Empty when the overlay declared no bridges or has not been applied.
+    async fn get_bridge_status(&self, overlay_handle: &str) -> Result<String, fdo::Error> {
+        trace!("get_bridge_status called for {overlay_handle}");
+        let handles = universal_overlay_handler::overlay_bridges(overlay_handle);
+        let states = universal_bridge::bridge_states(&handles)?;
+        Ok(states
+            .into_iter()
+            .map(|(h, enabled)| format!("{h} {}", if enabled { "enabled" } else { "disabled" }))
+            .collect::<Vec<_>>()
+            .join("\n"))
+    }
+
+    /// Returns a coarse 0..=100 programming-progress estimate for `device_handle`,
+    /// derived from the typed FPGA state machine.
+    async fn get_programming_progress(&self, device_handle: &str) -> Result<u8, fdo::Error> {
+        trace!("get_programming_progress called with name: {device_handle}");
+        validate_device_handle(device_handle)?;
+        Ok(new_platform(device_handle)
+            .fpga(device_handle)?
+            .state_enum()?
+            .progress_percent())
+    }
+
+    /// Returns a hex-encoded readback of the bitstream currently programmed on
+    /// `device_handle`, for platforms whose driver supports readback.
+    async fn get_bitstream_readback(&self, device_handle: &str) -> Result<String, fdo::Error> {
+        trace!("get_bitstream_readback called with name: {device_handle}");
+        validate_device_handle(device_handle)?;
+        let bytes = new_platform(device_handle).fpga(device_handle)?.readback()?;
+        Ok(bytes.iter().map(|b| format!("{b:02x}")).collect())
+    }
+
+    /// Lists the FPGA regions present on the system, one handle per line.
+    async fn list_regions(&self) -> Result<String, fdo::Error> {
+        trace!("list_regions called");
+        Ok(fpga_region::list_regions()?.join("\n"))
+    }
+
+    /// Lists every FPGA region with its resolved manager and declared bridges,
+    /// one `<region> mgr=<handle|?> bridges=<a,b,..>` record per line, so clients
+    /// can discover the region→manager→bridge topology before programming.
+    async fn get_region_info(&self) -> Result<String, fdo::Error> {
+        trace!("get_region_info called");
+        let lines = crate::platforms::platform::list_fpga_regions()?
+            .into_iter()
+            .map(|r| {
+                format!(
+                    "{} mgr={} bridges={}",
+                    r.region_handle,
+                    r.manager_handle.as_deref().unwrap_or("?"),
+                    r.bridges.join(",")
+                )
+            })
+            .collect::<Vec<_>>();
+        Ok(lines.join("\n"))
+    }
+
+    /// Returns the device handle of the fpga_manager owned by `region_handle`,
+    /// resolved through the region's `fpga-mgr` phandle.
+    async fn get_region_manager(&self, region_handle: &str) -> Result<String, fdo::Error> {
+        trace!("get_region_manager called with region_handle: {region_handle}");
+        UniversalRegion::new(region_handle)
+            .manager()?
+            .ok_or_else(|| {
+                fdo::Error::from(crate::error::FpgadError::Argument(format!(
+                    "region '{region_handle}' has no resolvable fpga-mgr"
+                )))
+            })
+    }
+
+    /// Lists the bridges `region_handle` owns, resolved through its
+    /// `fpga-bridges` phandles, one handle per line. These are the bridges the
+    /// region disables around a reconfiguration, so clients can inspect the
+    /// isolation topology before programming.
+    async fn get_region_bridges(&self, region_handle: &str) -> Result<String, fdo::Error> {
+        trace!("get_region_bridges called with region_handle: {region_handle}");
+        Ok(fpga_region::region_bridges(region_handle)?.join("\n"))
+    }
+
+    /// Lists the overlay handles fpgad has applied under `region_handle`.
+    async fn get_region_overlays(&self, region_handle: &str) -> Result<String, fdo::Error> {
+        trace!("get_region_overlays called with region_handle: {region_handle}");
+        Ok(UniversalRegion::new(region_handle).overlays()?.join("\n"))
+    }
+
+    /// Returns the design UUID and section summary of the xclbin most recently
+    /// programmed onto `device_handle`. Errors when the last load was a raw
+    /// bitstream carrying no container metadata.
+    async fn get_loaded_design_info(&self, device_handle: &str) -> Result<String, fdo::Error> {
+        trace!("get_loaded_design_info called with name: {device_handle}");
+        validate_device_handle(device_handle)?;
+        Ok(xclbin::loaded_design_info(device_handle)?.summary())
+    }
+
+    /// Reports the firmware an applied overlay pulled in via its `firmware-name`
+    /// property, or an error when the overlay named no firmware (or has not been
+    /// applied this session).
+    async fn get_overlay_firmware(&self, overlay_handle: &str) -> Result<String, fdo::Error> {
+        trace!("get_overlay_firmware called with overlay_handle: {overlay_handle}");
+        universal_overlay_handler::overlay_firmware(overlay_handle).ok_or_else(|| {
+            fdo::Error::from(crate::error::FpgadError::Argument(format!(
+                "no firmware recorded for overlay '{overlay_handle}'"
+            )))
+        })
+    }
+
+    /// Lists the accelerator packages downloaded locally for the Xilinx DFX
+    /// manager, as reported by dfx-mgr-client.
+    #[cfg(feature = "softeners")]
+    async fn dfx_list_packages(&self) -> Result<String, fdo::Error> {
+        trace!("dfx_list_packages called");
+        Ok(xilinx_dfx_mgr::list_package().map_err(crate::error::FpgadError::Softener)?)
+    }
+
+    /// Lists the UIOs belonging to an accelerator slot. An empty `uio_name`
+    /// lists all UIOs; `slot` of `u32::MAX` means "unspecified".
+    #[cfg(feature = "softeners")]
+    async fn dfx_list_uio(&self, slot: u32, uio_name: &str) -> Result<String, fdo::Error> {
+        trace!("dfx_list_uio called slot {slot} name '{uio_name}'");
+        let slot = (slot != u32::MAX).then_some(slot);
+        let uio_name = (!uio_name.is_empty()).then_some(uio_name);
+        Ok(xilinx_dfx_mgr::list_uio(slot, uio_name).map_err(crate::error::FpgadError::Softener)?)
+    }
+
+    /// Lists inter-RM buffer information, optionally scoped to one slot
+    /// (`u32::MAX` means "all slots").
+    #[cfg(feature = "softeners")]
+    async fn dfx_list_irbuf(&self, slot: u32) -> Result<String, fdo::Error> {
+        trace!("dfx_list_irbuf called slot {slot}");
+        let slot = (slot != u32::MAX).then_some(slot);
+        Ok(xilinx_dfx_mgr::list_irbuf(slot).map_err(crate::error::FpgadError::Softener)?)
+    }
+
+    /// Returns the dfx-mgr reconfigurable-module occupancy table (slot, loaded
+    /// package, RM info) for dynamic-function-exchange platforms.
+    #[cfg(feature = "softeners")]
+    async fn dfx_get_rm_info(&self) -> Result<String, fdo::Error> {
+        trace!("dfx_get_rm_info called");
+        Ok(xilinx_dfx_mgr::get_rm_info().map_err(crate::error::FpgadError::Softener)?)
+    }
+
+    /// Returns the most recently sampled value of each monitored health
+    /// property, one `<path>=<value>` pair per line. Empty when the health
+    /// monitor is not configured or has not yet polled.
+    async fn get_monitored_values(&self) -> Result<String, fdo::Error> {
+        trace!("get_monitored_values called");
+        Ok(crate::health_monitor::monitored_values_report())
+    }
+
+    /// Returns a description of the last safe-fallback action the health monitor
+    /// took, or a note that it has not triggered this session.
+    async fn get_last_health_action(&self) -> Result<String, fdo::Error> {
+        trace!("get_last_health_action called");
+        Ok(crate::health_monitor::last_triggered_action()
+            .unwrap_or_else(|| "no health fallback triggered".to_string()))
+    }
+
+    /// Returns the status of the current or last secure flash update, or a note
+    /// that none has been requested this session. Flash updates persist an
+    /// authenticated image to flash and are tracked separately from the volatile
+    /// bitstream loading path.
+    async fn get_flash_update_status(&self) -> Result<String, fdo::Error> {
+        trace!("get_flash_update_status called");
+        Ok(crate::flash_update::last_update_status()
+            .unwrap_or_else(|| "no flash update requested".to_string()))
+    }
+
+    /// Returns a machine-readable inventory of the FPGA devices as a JSON array,
+    /// one object per manager with the schema
+    /// `{"handle":str,"compatible":str,"state":str,"flags":int,"error":{"code":int,"name":str}}`.
+    /// This replaces hand-parsing the newline-joined getters, and the `error`
+    /// field reports the decoded [`DriverError`] identity rather than leaking a
+    /// raw OS code.
+    async fn get_device_inventory(&self) -> Result<String, fdo::Error> {
+        trace!("get_device_inventory called");
+        let mut records = Vec::new();
+        for handle in crate::platforms::platform::list_fpga_managers()? {
+            let platform = new_platform(&handle);
+            let fpga = platform.fpga(&handle)?;
+            let compatible =
+                crate::platforms::platform::read_compatible_string(&handle).unwrap_or_default();
+            let state = fpga.state().unwrap_or_else(|_| "unknown".to_string());
+            let flags = fpga.flags().unwrap_or(0);
+            let error = crate::error::DriverError::decode(read_driver_status_code(&handle));
+            records.push(format!(
+                "{{\"handle\":\"{}\",\"compatible\":\"{}\",\"state\":\"{}\",\"flags\":{},\
+                 \"error\":{{\"code\":{},\"name\":\"{}\"}}}}",
+                json_escape(&handle),
+                json_escape(&compatible),
+                json_escape(state.trim()),
+                flags,
+                error.code(),
+                error.name(),
+            ));
+        }
+        Ok(format!("[{}]", records.join(",")))
+    }
+
+    /// Returns the applied overlays as a JSON array of
+    /// `{"handle":str,"status":str}` objects, the structured counterpart to the
+    /// newline-joined `get_overlays` getter.
+    async fn get_overlays_detailed(&self) -> Result<String, fdo::Error> {
+        trace!("get_overlays_detailed called");
+        let mut records = Vec::new();
+        for handle in crate::system_io::fs_read_dir(crate::config::OVERLAY_CONTROL_DIR.as_ref())
+            .unwrap_or_default()
+        {
+            let status = universal_overlay_handler::UniversalOverlayHandler::new(&handle)
+                .status()
+                .unwrap_or_else(|_| "unknown".to_string());
+            records.push(format!(
+                "{{\"handle\":\"{}\",\"status\":\"{}\"}}",
+                json_escape(&handle),
+                json_escape(&status),
+            ));
+        }
+        Ok(format!("[{}]", records.join(",")))
+    }
+
     async fn get_overlay_status(
         &self,
         device_handle: &str,
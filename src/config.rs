@@ -10,7 +10,20 @@
 //
 // You should have received a copy of the GNU General Public License along with this program.  If not, see http://www.gnu.org/licenses/.
 
+pub mod boot_firmware;
+pub mod config_files;
+pub mod system_config;
+
 pub static FIRMWARE_SOURCE_DIR: &str = "/lib/firmware/";
 pub static FPGA_MANAGERS_DIR: &str = "/sys/class/fpga_manager/";
+pub static FPGA_BRIDGES_DIR: &str = "/sys/class/fpga_bridge/";
+pub static FPGA_REGIONS_DIR: &str = "/sys/class/fpga_region/";
+/// The FPGA security-manager class, which exposes the authenticated flash/BMC
+/// update channel (`update/filename`, `update/status`, ...) that persists an
+/// image to flash rather than loading it into the fabric.
+pub static FPGA_SEC_MGR_DIR: &str = "/sys/class/fpga_sec_mgr/";
 pub static OVERLAY_CONTROL_DIR: &str = "/sys/kernel/config/device-tree/overlays/";
 pub static FIRMWARE_LOC_CONTROL_PATH: &str = "/sys/module/firmware_class/parameters/path";
+/// Directory scanned, on the first signature check, for PEM-encoded public keys
+/// trusted to sign bitstreams. Operators drop one file per signing key here.
+pub static TRUSTED_KEYS_DIR: &str = "/etc/fpgad/trusted-keys/";
@@ -2,11 +2,13 @@ use crate::config::boot_firmware;
 use crate::config::config_files::{boot_firmware_from_file, BootFirmware};
 use crate::error::FpgadError;
 use crate::platforms::platform::new_platform;
+use crate::platforms::platform::platform_for_known_platform;
 use crate::platforms::platform::Fpga;
 use crate::platforms::platform::OverlayHandler;
 use crate::platforms::platform::Platform;
 use crate::system_io::validate_device_handle;
-use log::{info, trace};
+use log::{error, info, trace};
+use std::collections::HashSet;
 use std::path::{Path, PathBuf};
 
 pub fn boot_firmware() -> BootFirmware {
@@ -17,6 +19,113 @@ pub fn boot_firmware() -> BootFirmware {
     user_config.merge(vendor_config)
 }
 
+/// Loads the `[boot_firmware]` config from the user file, falling back to the
+/// vendor file and finally to an empty config, so a board with no boot section
+/// simply applies nothing.
+fn configured_boot_firmware() -> BootFirmware {
+    boot_firmware_from_file(&PathBuf::from("/etc/fpgad/config.toml"))
+        .or_else(|_| boot_firmware_from_file(&PathBuf::from("/usr/lib/fpgad/config.toml")))
+        .unwrap_or_else(|_| BootFirmware::default())
+}
+
+/// Applies the configured boot firmware at daemon start, mirroring how a
+/// bootloader brings up FPGA content without an external orchestrator.
+///
+/// Bitstreams are loaded first: the distinct `(device_handle, bitstream_path)`
+/// pairs are collected and deduplicated so a bitstream referenced more than once
+/// is only written to the fabric a single time, then applied in a single pass.
+/// Overlays follow, in config order. Per-entry `flags`/`fpga_flags` and the
+/// overlay `platform`/`device_handle` are honored. A failure on any entry is
+/// logged and skipped, unless `abort_on_error` is set, in which case it stops
+/// the sequence and propagates.
+pub fn apply_boot_firmware() -> Result<String, FpgadError> {
+    let boot_firmware = configured_boot_firmware();
+    let mut report = String::new();
+
+    // Deduplicate bitstream loads by (manager, firmware) so a shared bitstream
+    // is only programmed once, preserving first-seen order.
+    let mut seen: HashSet<(String, String)> = HashSet::new();
+    for bitstream in &boot_firmware.bitstreams {
+        let key = (
+            bitstream.device_handle.clone(),
+            bitstream.bitstream_path.clone(),
+        );
+        if !seen.insert(key) {
+            trace!(
+                "skipping already-loaded bitstream '{}' on {}",
+                bitstream.bitstream_path,
+                bitstream.device_handle
+            );
+            continue;
+        }
+        match load_boot_bitstream(bitstream) {
+            Ok(msg) => {
+                info!("{msg}");
+                report.push_str(&msg);
+                report.push('\n');
+            }
+            Err(e) if boot_firmware.abort_on_error => return Err(e),
+            Err(e) => {
+                error!("boot bitstream '{}' failed: {e}", bitstream.bitstream_path);
+                report.push_str(&format!("FAILED {}: {e}\n", bitstream.bitstream_path));
+            }
+        }
+    }
+
+    for overlay in &boot_firmware.overlays {
+        match apply_boot_overlay(overlay) {
+            Ok(msg) => {
+                info!("{msg}");
+                report.push_str(&msg);
+                report.push('\n');
+            }
+            Err(e) if boot_firmware.abort_on_error => return Err(e),
+            Err(e) => {
+                error!("boot overlay '{}' failed: {e}", overlay.overlay_path);
+                report.push_str(&format!("FAILED {}: {e}\n", overlay.overlay_path));
+            }
+        }
+    }
+
+    Ok(report)
+}
+
+/// Programs a single configured boot bitstream onto its device.
+fn load_boot_bitstream(
+    bitstream: &crate::config::config_files::Bitstream,
+) -> Result<String, FpgadError> {
+    validate_device_handle(&bitstream.device_handle)?;
+    let platform = new_platform(&bitstream.device_handle)?;
+    let fpga = platform.fpga(&bitstream.device_handle)?;
+    let flags = u32::try_from(bitstream.flags)
+        .map_err(|_| FpgadError::Argument(format!("bitstream flags {} out of range", bitstream.flags)))?;
+    fpga.set_flags(flags)?;
+    fpga.load_firmware(Path::new(&bitstream.bitstream_path))?;
+    Ok(format!(
+        "{} loaded to {}",
+        bitstream.bitstream_path, bitstream.device_handle
+    ))
+}
+
+/// Applies a single configured boot overlay, honoring its platform, optional
+/// device handle and flags.
+fn apply_boot_overlay(overlay: &crate::config::config_files::Overlay) -> Result<String, FpgadError> {
+    let platform = platform_for_known_platform(&overlay.platform)?;
+    if let (Some(device_handle), Some(flags)) = (&overlay.device_handle, overlay.fpga_flags) {
+        validate_device_handle(device_handle)?;
+        let flags = u32::try_from(flags)
+            .map_err(|_| FpgadError::Argument(format!("overlay fpga_flags {flags} out of range")))?;
+        platform.fpga(device_handle)?.set_flags(flags)?;
+    }
+    let overlay_handler = platform.overlay_handler(&overlay.overlay_handle)?;
+    let overlay_fs_path = overlay_handler.overlay_fs_path()?.to_path_buf();
+    overlay_handler.apply_overlay(Path::new(&overlay.overlay_path))?;
+    Ok(format!(
+        "{} applied via {overlay_fs_path:?}",
+        overlay.overlay_path
+    ))
+}
+
 pub fn load_defaults() -> Result<String, FpgadError> {
     let mut ret_string = String::new();
     let boot_firmware = boot_firmware::boot_firmware();
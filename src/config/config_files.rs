@@ -11,6 +11,32 @@ use std::sync::Mutex;
 pub(crate) struct TomlConfig {
     system_paths: Option<SystemPaths>,
     boot_firmware: Option<BootFirmware>,
+    health_monitor: Option<HealthMonitor>,
+}
+
+/// The `[health_monitor]` section: an opt-in background watcher that polls a set
+/// of device sysfs properties and drives the part to a safe fallback image when
+/// a reading breaches its threshold. Absent section means monitoring is off.
+#[derive(Debug, Deserialize, Clone)]
+pub(crate) struct HealthMonitor {
+    /// How often to sample the watched properties.
+    pub(crate) poll_interval_secs: u64,
+    /// The sysfs properties to sample and the thresholds that trigger a fallback.
+    pub(crate) watches: Vec<HealthWatch>,
+    /// Firmware-relative bitstream to program when any watch breaches, putting
+    /// the part into a known-good state. `None` removes active overlays instead.
+    pub(crate) fallback_bitstream: Option<String>,
+    /// Device the fallback bitstream is programmed onto.
+    pub(crate) fallback_device_handle: Option<String>,
+}
+
+/// A single monitored sysfs property and the threshold it must not exceed.
+#[derive(Debug, Deserialize, Clone)]
+pub(crate) struct HealthWatch {
+    /// Absolute sysfs path read each poll (e.g. a thermal or error-counter node).
+    pub(crate) path: String,
+    /// Inclusive upper bound; a sampled value above this triggers the fallback.
+    pub(crate) max: f64,
 }
 
 /// This is the "defaults" section struct
@@ -25,6 +51,11 @@ pub(crate) struct SystemPaths {
 pub(crate) struct BootFirmware {
     pub(crate) bitstreams: Vec<Bitstream>,
     pub(crate) overlays: Vec<Overlay>,
+    /// When true, a failure applying any entry aborts the boot sequence;
+    /// otherwise the failure is logged and the remaining entries are applied.
+    /// Defaults to continue-on-error so one bad entry does not strand the board.
+    #[serde(default)]
+    pub(crate) abort_on_error: bool,
 }
 
 #[derive(Debug, Deserialize, Clone)]
@@ -85,6 +116,7 @@ impl BootFirmware {
         BootFirmware {
             bitstreams: Vec::new(),
             overlays: Vec::new(),
+            abort_on_error: false,
         }
     }
 }
@@ -118,6 +150,21 @@ pub(crate) fn system_paths_config_from_file(file_path: &Path) -> Result<SystemPa
     }
 }
 
+pub(crate) fn health_monitor_from_file(file_path: &Path) -> Result<HealthMonitor, FpgadError> {
+    if !file_path.is_file() {
+        return Err(FpgadError::Internal(format!(
+            "Config file not found in {file_path:?}"
+        )));
+    }
+    let config = toml_str_to_config(&fs_read(file_path)?)?;
+    match config.health_monitor {
+        Some(health_monitor) => Ok(health_monitor),
+        None => Err(FpgadError::Internal(
+            "config file did not contain a `[health_monitor]` section.".to_string(),
+        )),
+    }
+}
+
 pub fn boot_firmware_from_file(file_path: &Path) -> Result<BootFirmware, FpgadError> {
     if !file_path.is_file() {
         return Err(FpgadError::Internal(format!(
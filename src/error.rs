@@ -10,18 +10,108 @@
 //
 // You should have received a copy of the GNU General Public License along with this program.  If not, see http://www.gnu.org/licenses/.
 
+use log::error;
+use std::path::PathBuf;
+use zbus::fdo;
+
 #[derive(Debug, thiserror::Error)]
 pub enum FpgadError {
-    #[error("Failed to read flags: {0}")]
-    FlagError(String),
-    #[error("Overlay was not applied: {0}")]
-    OverlayStatusError(String),
-    #[error("FPGA state is not as expected: {0}")]
-    FPGAStateError(String),
-    #[error("ArgumentError: {0}")]
-    ArgumentError(String),
-    #[error("An IO error occurred: {0}")]
-    IOError(String),
-    #[error("An Internal error occurred: {0}")]
-    InternalError(String),
+    #[error("FpgadError::Flag: Failed to read flags: {0}")]
+    Flag(String),
+    #[error("FpgadError::OverlayStatus: Overlay was not applied: {0}")]
+    OverlayStatus(String),
+    #[error("FpgadError::FPGAState: FPGA state is not as expected: {0}")]
+    FPGAState(String),
+    #[error("FpgadError::Argument: {0}")]
+    Argument(String),
+    #[error("FpgadError::IORead: An IO error occurred when reading from {file:?}: {e}")]
+    IORead { file: PathBuf, e: std::io::Error },
+    #[error("FpgadError::IOWrite: An IO error occurred when writing {data:?} to {file:?}: {e}")]
+    IOWrite {
+        data: String,
+        file: PathBuf,
+        e: std::io::Error,
+    },
+    #[error("FpgadError::IOCreate: An IO error occurred when creating {file:?}: {e}")]
+    IOCreate { file: PathBuf, e: std::io::Error },
+    #[error("FpgadError::IODelete: An IO error occurred when deleting {file:?}: {e}")]
+    IODelete { file: PathBuf, e: std::io::Error },
+    #[error("FpgadError::IOReadDir: An IO error occurred when reading directory {dir:?}: {e}")]
+    IOReadDir { dir: PathBuf, e: std::io::Error },
+    #[error("FpgadError::TomlDe: Failed to parse config {toml_string:?}: {e}")]
+    TomlDe {
+        toml_string: String,
+        e: toml::de::Error,
+    },
+    #[error("FpgadError::Verification: {0}")]
+    Verification(String),
+    #[error("FpgadError::Bridge: FPGA bridge error: {0}")]
+    Bridge(String),
+    #[cfg(feature = "softeners")]
+    #[error("FpgadError::Softener: An error occurred using softener: {0}")]
+    Softener(crate::softeners::error::FpgadSoftenerError),
+    #[error("FpgadError::Internal: An Internal error occurred: {0}")]
+    Internal(String),
+}
+
+/// A decoded FPGA-manager driver status code, so the status API and the string
+/// getters report a stable, machine-readable error identity instead of leaking
+/// a raw OS error like `0xfffff8fe`. Codes are the negative errno-style values
+/// the drivers return; unrecognised ones are preserved in [`DriverError::Other`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DriverError {
+    /// No error (code 0).
+    None,
+    /// Xilinx ZynqMP bitstream authentication failure, returned as `0xfffff8fe`
+    /// (`-1794`) by the PMU firmware when a signed image fails verification.
+    Authentication,
+    /// Any other driver code fpgad does not yet name.
+    Other(i32),
+}
+
+impl DriverError {
+    /// Decodes a raw driver status code into a named variant.
+    pub fn decode(code: i32) -> DriverError {
+        match code {
+            0 => DriverError::None,
+            -1794 => DriverError::Authentication,
+            other => DriverError::Other(other),
+        }
+    }
+
+    /// A stable identifier for the decoded error.
+    pub fn name(self) -> String {
+        match self {
+            DriverError::None => "none".to_string(),
+            DriverError::Authentication => "authentication-error".to_string(),
+            DriverError::Other(code) => format!("driver-error-{code}"),
+        }
+    }
+
+    /// The raw driver status code this decodes from.
+    pub fn code(self) -> i32 {
+        match self {
+            DriverError::None => 0,
+            DriverError::Authentication => -1794,
+            DriverError::Other(code) => code,
+        }
+    }
+}
+
+impl From<FpgadError> for fdo::Error {
+    fn from(err: FpgadError) -> Self {
+        error!("{err}");
+        match err {
+            FpgadError::Argument(..) => fdo::Error::InvalidArgs(err.to_string()),
+            FpgadError::IORead { .. }
+            | FpgadError::IOWrite { .. }
+            | FpgadError::IOCreate { .. }
+            | FpgadError::IODelete { .. }
+            | FpgadError::IOReadDir { .. } => fdo::Error::IOError(err.to_string()),
+            // A failed integrity/signature check must never look like a transient
+            // error: surface it as an access-control denial so clients do not retry.
+            FpgadError::Verification(..) => fdo::Error::AccessDenied(err.to_string()),
+            _ => fdo::Error::Failed(err.to_string()),
+        }
+    }
 }
@@ -0,0 +1,140 @@
+// This file is part of fpgad, an application to manage FPGA subsystem together with device-tree and kernel modules.
+//
+// Copyright 2025 Canonical Ltd.
+//
+// SPDX-License-Identifier: GPL-3.0-only
+//
+// fpgad is free software: you can redistribute it and/or modify it under the terms of the GNU General Public License version 3, as published by the Free Software Foundation.
+//
+// fpgad is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY; without even the implied warranties of MERCHANTABILITY, SATISFACTORY QUALITY, or FITNESS FOR A PARTICULAR PURPOSE.  See the GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License along with this program.  If not, see http://www.gnu.org/licenses/.
+
+//! fwupd/LVFS-style firmware packages.
+//!
+//! A package is a CAB archive carrying the bitstream payload alongside a small
+//! `metadata.toml` describing it: a firmware id and version, the SHA-256 of the
+//! payload, and optional target constraints. The payload is never written to a
+//! device until its checksum matches the declared digest and the target
+//! constraints agree with the requested device, giving fpgad the integrity and
+//! metadata guarantees of a firmware-update daemon without depending on one.
+
+use crate::error::FpgadError;
+use crate::system_io::validate_device_handle;
+use log::{info, trace};
+use serde::Deserialize;
+use sha2::{Digest, Sha256};
+use std::io::Read;
+use std::path::Path;
+
+/// Name of the metadata member inside the package archive.
+const METADATA_MEMBER: &str = "metadata.toml";
+
+/// The metadata member of a firmware package.
+#[derive(Debug, Deserialize)]
+pub struct PackageMetadata {
+    /// Stable identifier of the firmware (e.g. a reverse-DNS string).
+    pub firmware_id: String,
+    /// Human-readable firmware version.
+    pub version: String,
+    /// Lowercase-hex SHA-256 of the payload member.
+    pub sha256: String,
+    /// Name of the payload member inside the archive.
+    pub payload: String,
+    /// Platform string the firmware is built for, if constrained.
+    pub platform: Option<String>,
+    /// Device handle the firmware is pinned to, if constrained.
+    pub device_handle: Option<String>,
+}
+
+/// A package whose checksum and constraints have been verified, ready to stream
+/// into the normal programming path.
+pub struct VerifiedFirmware {
+    pub firmware_id: String,
+    pub version: String,
+    pub payload: Vec<u8>,
+}
+
+impl VerifiedFirmware {
+    /// The `id version` string reported to callers on a successful load.
+    pub fn id_version(&self) -> String {
+        format!("{} {}", self.firmware_id, self.version)
+    }
+}
+
+/// Reads a named member out of the CAB archive at `package_path`.
+fn read_member(package_path: &Path, member: &str) -> Result<Vec<u8>, FpgadError> {
+    let file = std::fs::File::open(package_path).map_err(|e| {
+        FpgadError::Verification(format!("could not open package {package_path:?}: {e}"))
+    })?;
+    let mut cabinet = cab::Cabinet::new(file)
+        .map_err(|e| FpgadError::Verification(format!("malformed CAB package: {e}")))?;
+    let mut reader = cabinet
+        .read_file(member)
+        .map_err(|e| FpgadError::Verification(format!("package has no '{member}' member: {e}")))?;
+    let mut bytes = Vec::new();
+    reader
+        .read_to_end(&mut bytes)
+        .map_err(|e| FpgadError::Verification(format!("could not read '{member}': {e}")))?;
+    Ok(bytes)
+}
+
+/// Lowercase-hex SHA-256 of `bytes`.
+fn sha256_hex(bytes: &[u8]) -> String {
+    let digest = Sha256::digest(bytes);
+    digest.iter().map(|b| format!("{b:02x}")).collect()
+}
+
+/// Opens the package, verifies the payload checksum against the declared digest,
+/// checks the target constraints against the requested device and platform, and
+/// returns the verified payload. Any mismatch yields [`FpgadError::Verification`]
+/// and nothing is ever written.
+pub fn verify_package(
+    package_path: &Path,
+    device_handle: &str,
+    platform_string: &str,
+) -> Result<VerifiedFirmware, FpgadError> {
+    validate_device_handle(device_handle)?;
+    let metadata_bytes = read_member(package_path, METADATA_MEMBER)?;
+    let metadata_str = std::str::from_utf8(&metadata_bytes)
+        .map_err(|e| FpgadError::Verification(format!("{METADATA_MEMBER} is not UTF-8: {e}")))?;
+    let metadata: PackageMetadata = toml::from_str(metadata_str)
+        .map_err(|e| FpgadError::Verification(format!("malformed {METADATA_MEMBER}: {e}")))?;
+
+    if let Some(ref want) = metadata.device_handle {
+        if want != device_handle {
+            return Err(FpgadError::Verification(format!(
+                "package targets device '{want}' but load requested '{device_handle}'"
+            )));
+        }
+    }
+    if let Some(ref want) = metadata.platform {
+        if !platform_string.is_empty() && want != platform_string {
+            return Err(FpgadError::Verification(format!(
+                "package targets platform '{want}' but load requested '{platform_string}'"
+            )));
+        }
+    }
+
+    let payload = read_member(package_path, &metadata.payload)?;
+    let actual = sha256_hex(&payload);
+    if !actual.eq_ignore_ascii_case(&metadata.sha256) {
+        return Err(FpgadError::Verification(format!(
+            "payload SHA-256 {actual} does not match declared {}",
+            metadata.sha256
+        )));
+    }
+
+    info!(
+        "verified firmware package '{}' version '{}' ({} payload bytes)",
+        metadata.firmware_id,
+        metadata.version,
+        payload.len()
+    );
+    trace!("package constraints satisfied for {device_handle}");
+    Ok(VerifiedFirmware {
+        firmware_id: metadata.firmware_id,
+        version: metadata.version,
+        payload,
+    })
+}
@@ -0,0 +1,136 @@
+// This file is part of fpgad, an application to manage FPGA subsystem together with device-tree and kernel modules.
+//
+// Copyright 2025 Canonical Ltd.
+//
+// SPDX-License-Identifier: GPL-3.0-only
+//
+// fpgad is free software: you can redistribute it and/or modify it under the terms of the GNU General Public License version 3, as published by the Free Software Foundation.
+//
+// fpgad is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY; without even the implied warranties of MERCHANTABILITY, SATISFACTORY QUALITY, or FITNESS FOR A PARTICULAR PURPOSE.  See the GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License along with this program.  If not, see http://www.gnu.org/licenses/.
+
+//! Secure flash/BMC image updates.
+//!
+//! The FPGA security-manager class exposes an update channel for authenticated
+//! images (BMC, SDM, flash) that is distinct from live fabric reconfiguration:
+//! the image is persisted to flash rather than loaded into the programmable
+//! region. The operator writes a firmware-lookup name into `update/filename`,
+//! the driver runs an authenticated multi-phase update (`preparing`,
+//! `transferring`, `programming`), and progress is observed through
+//! `update/status` and `update/remaining_size`, with `update/error` holding the
+//! terminal failure reason. This mirrors the Intel `fpga_sec_mgr` sysfs layout.
+
+use crate::config::FPGA_SEC_MGR_DIR;
+use crate::error::FpgadError;
+use crate::system_io::{fs_read, fs_write};
+use log::{info, trace};
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, OnceLock};
+use std::time::{Duration, Instant};
+
+/// Longest a flash/BMC update is allowed to run before fpgad gives up waiting.
+/// These updates persist to flash rather than the fabric and can legitimately
+/// take minutes, but an unbounded wait would let a stuck driver hang the
+/// status-polling caller forever.
+const UPDATE_TIMEOUT: Duration = Duration::from_secs(600);
+
+/// The status of the most recent flash update fpgad started, for the status
+/// interface to report while one is in flight or after it completes.
+static LAST_STATUS: OnceLock<Mutex<Option<String>>> = OnceLock::new();
+
+fn last_status() -> &'static Mutex<Option<String>> {
+    LAST_STATUS.get_or_init(|| Mutex::new(None))
+}
+
+fn record_status(status: String) {
+    trace!("flash update: {status}");
+    if let Ok(mut last) = last_status().lock() {
+        *last = Some(status);
+    }
+}
+
+/// The status of the current or last flash update, or `None` if no update has
+/// been requested this session.
+pub fn last_update_status() -> Option<String> {
+    last_status().lock().ok().and_then(|s| s.clone())
+}
+
+/// The sysfs directory of a security manager, e.g.
+/// `/sys/class/fpga_sec_mgr/fpga_sec0/`.
+fn sec_mgr_dir(sec_mgr_handle: &str) -> PathBuf {
+    Path::new(FPGA_SEC_MGR_DIR).join(sec_mgr_handle)
+}
+
+/// Reads an `update/<node>` property, tolerating the trailing newline.
+fn read_update_node(dir: &Path, node: &str) -> Result<String, FpgadError> {
+    Ok(fs_read(&dir.join("update").join(node))?.trim().to_string())
+}
+
+/// Kicks off an authenticated flash update on `sec_mgr_handle` by writing the
+/// firmware-lookup name `filename` into `update/filename`. This is the only
+/// part of the update that touches the driver's write-triggered node, so it is
+/// the only part callers need to serialize against other reconfigurations;
+/// [`await_completion`] only reads status nodes and can run without holding
+/// that lock.
+pub fn start_update(sec_mgr_handle: &str, filename: &str) -> Result<(), FpgadError> {
+    let dir = sec_mgr_dir(sec_mgr_handle);
+    if !dir.is_dir() {
+        return Err(FpgadError::Argument(format!(
+            "security manager {sec_mgr_handle} not found under {FPGA_SEC_MGR_DIR}"
+        )));
+    }
+    record_status(format!("{sec_mgr_handle}: preparing '{filename}'"));
+    fs_write(&dir.join("update").join("filename"), false, filename)
+}
+
+/// Polls `update/status`/`update/remaining_size` until the driver returns to
+/// `idle`, recording each observed phase for the status interface, and gives
+/// up with [`FpgadError::FPGAState`] if that takes longer than
+/// [`UPDATE_TIMEOUT`] so a stuck driver cannot hang the caller forever. On
+/// completion the `update/error` node is consulted: a non-`none` value is
+/// surfaced as a [`FpgadError::Verification`] so a failed authentication does
+/// not look transient.
+pub async fn await_completion(sec_mgr_handle: &str, filename: &str) -> Result<String, FpgadError> {
+    let dir = sec_mgr_dir(sec_mgr_handle);
+    let deadline = Instant::now() + UPDATE_TIMEOUT;
+
+    // The driver reports `idle` before starting and again once the update is
+    // complete; everything in between is a live phase we surface verbatim.
+    let mut started = false;
+    loop {
+        let status = read_update_node(&dir, "status")?;
+        if status == "idle" {
+            if started {
+                break;
+            }
+        } else {
+            started = true;
+            let remaining = read_update_node(&dir, "remaining_size").unwrap_or_default();
+            record_status(format!(
+                "{sec_mgr_handle}: {status} ({remaining} bytes remaining)"
+            ));
+        }
+        if Instant::now() >= deadline {
+            let timed_out = format!(
+                "{sec_mgr_handle}: update of '{filename}' did not reach 'idle' within {UPDATE_TIMEOUT:?}"
+            );
+            record_status(timed_out.clone());
+            return Err(FpgadError::FPGAState(timed_out));
+        }
+        tokio::time::sleep(Duration::from_millis(500)).await;
+    }
+
+    let error = read_update_node(&dir, "error").unwrap_or_default();
+    if !error.is_empty() && error != "none" {
+        record_status(format!("{sec_mgr_handle}: failed: {error}"));
+        return Err(FpgadError::Verification(format!(
+            "flash update of '{filename}' failed: {error}"
+        )));
+    }
+
+    let done = format!("{sec_mgr_handle}: completed '{filename}'");
+    info!("{done}");
+    record_status(done.clone());
+    Ok(done)
+}
@@ -0,0 +1,182 @@
+// This file is part of fpgad, an application to manage FPGA subsystem together with device-tree and kernel modules.
+//
+// Copyright 2025 Canonical Ltd.
+//
+// SPDX-License-Identifier: GPL-3.0-only
+//
+// fpgad is free software: you can redistribute it and/or modify it under the terms of the GNU General Public License version 3, as published by the Free Software Foundation.
+//
+// fpgad is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY; without even the implied warranties of MERCHANTABILITY, SATISFACTORY QUALITY, or FITNESS FOR A PARTICULAR PURPOSE.  See the GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License along with this program.  If not, see http://www.gnu.org/licenses/.
+
+//! Background health monitoring.
+//!
+//! An opt-in async task samples a configured set of device sysfs properties
+//! (error counters, thermal/power sensors) each poll and, when a reading
+//! breaches its threshold, drives the part into a safe state by programming a
+//! configured fallback bitstream or removing the active overlays. This mirrors
+//! the Intel fpgad daemon's watch-and-fall-back behaviour. The latest sampled
+//! values and the last triggered action are recorded for the status interface.
+
+use crate::comm::dbus::control_interface::get_write_lock_guard;
+use crate::config;
+use crate::config::config_files::{health_monitor_from_file, HealthMonitor};
+use crate::error::FpgadError;
+use crate::platforms::platform::{platform_from_compat_or_device, Fpga, OverlayHandler};
+use crate::platforms::universal_components::universal_overlay_handler::UniversalOverlayHandler;
+use crate::system_io::{fs_read, fs_read_dir};
+use log::{error, info, trace, warn};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, OnceLock};
+use std::time::Duration;
+
+/// The most recent sampled value of each watched property, keyed by sysfs path.
+static MONITORED_VALUES: OnceLock<Mutex<HashMap<String, f64>>> = OnceLock::new();
+/// A human-readable description of the last safe-fallback action fpgad took.
+static LAST_ACTION: OnceLock<Mutex<Option<String>>> = OnceLock::new();
+
+fn monitored_values() -> &'static Mutex<HashMap<String, f64>> {
+    MONITORED_VALUES.get_or_init(|| Mutex::new(HashMap::new()))
+}
+
+fn last_action() -> &'static Mutex<Option<String>> {
+    LAST_ACTION.get_or_init(|| Mutex::new(None))
+}
+
+fn record_value(path: &str, value: f64) {
+    if let Ok(mut map) = monitored_values().lock() {
+        map.insert(path.to_string(), value);
+    }
+}
+
+fn record_action(action: String) {
+    info!("health monitor: {action}");
+    if let Ok(mut last) = last_action().lock() {
+        *last = Some(action);
+    }
+}
+
+/// The most recently sampled value of each monitored property, one
+/// `<path>=<value>` pair per line.
+pub fn monitored_values_report() -> String {
+    monitored_values()
+        .lock()
+        .map(|m| {
+            m.iter()
+                .map(|(p, v)| format!("{p}={v}"))
+                .collect::<Vec<_>>()
+                .join("\n")
+        })
+        .unwrap_or_default()
+}
+
+/// The last safe-fallback action the monitor took, or `None` if it has not
+/// triggered this session.
+pub fn last_triggered_action() -> Option<String> {
+    last_action().lock().ok().and_then(|a| a.clone())
+}
+
+/// Loads the `[health_monitor]` config from the vendor and user config files,
+/// preferring the user file. Returns `None` when neither declares the section,
+/// i.e. monitoring is not opted into.
+pub fn configured_monitor() -> Option<HealthMonitor> {
+    health_monitor_from_file(&PathBuf::from("/etc/fpgad/config.toml"))
+        .or_else(|_| health_monitor_from_file(&PathBuf::from("/usr/lib/fpgad/config.toml")))
+        .ok()
+}
+
+/// Samples every watch once, recording the reading and returning the first watch
+/// whose value breaches its `max`, if any.
+fn poll_once(monitor: &HealthMonitor) -> Option<String> {
+    let mut breached = None;
+    for watch in &monitor.watches {
+        match read_value(Path::new(&watch.path)) {
+            Ok(value) => {
+                record_value(&watch.path, value);
+                if value > watch.max && breached.is_none() {
+                    breached = Some(watch.path.clone());
+                }
+            }
+            Err(e) => trace!("could not sample health property {}: {e}", watch.path),
+        }
+    }
+    breached
+}
+
+/// Reads a sysfs property and parses it as a floating-point reading, tolerating
+/// the trailing newline the kernel appends.
+fn read_value(path: &Path) -> Result<f64, FpgadError> {
+    fs_read(path)?
+        .trim()
+        .parse::<f64>()
+        .map_err(|e| FpgadError::Argument(format!("{path:?} is not a numeric sensor value: {e}")))
+}
+
+/// Puts the part into a safe state after a threshold breach: programs the
+/// configured fallback bitstream, or, when none is configured, removes the
+/// active overlays. The programming is done under the same write lock the D-Bus
+/// control path takes, so a threshold breach never reconfigures the fabric
+/// concurrently with an in-flight load.
+async fn apply_fallback(monitor: &HealthMonitor, breached_path: &str) {
+    match (&monitor.fallback_bitstream, &monitor.fallback_device_handle) {
+        (Some(bitstream), Some(device_handle)) => {
+            let _guard = get_write_lock_guard().await;
+            let result = platform_from_compat_or_device("", device_handle)
+                .and_then(|p| Ok(p.fpga(device_handle)?.load_firmware(Path::new(bitstream))?));
+            match result {
+                Ok(()) => record_action(format!(
+                    "{breached_path} breached; programmed fallback '{bitstream}' onto {device_handle}"
+                )),
+                Err(e) => error!("health monitor failed to program fallback '{bitstream}': {e}"),
+            }
+        }
+        _ => {
+            let _guard = get_write_lock_guard().await;
+            let handles = fs_read_dir(config::OVERLAY_CONTROL_DIR.as_ref()).unwrap_or_default();
+            let mut removed = Vec::new();
+            let mut failed = Vec::new();
+            for handle in handles {
+                match UniversalOverlayHandler::new(&handle).remove_overlay() {
+                    Ok(()) => removed.push(handle),
+                    Err(e) => {
+                        error!("health monitor failed to remove overlay '{handle}': {e}");
+                        failed.push(handle);
+                    }
+                }
+            }
+            if failed.is_empty() {
+                record_action(format!(
+                    "{breached_path} breached; no fallback bitstream configured, removed overlays [{}]",
+                    removed.join(", ")
+                ));
+            } else {
+                record_action(format!(
+                    "{breached_path} breached; no fallback bitstream configured, removed overlays [{}], failed to remove [{}]",
+                    removed.join(", "),
+                    failed.join(", ")
+                ));
+            }
+        }
+    }
+}
+
+/// Runs the monitor loop forever, sampling every `poll_interval_secs` and
+/// applying the fallback on the first breach of each poll. Intended to be
+/// spawned as a background task from `main`.
+pub async fn run(monitor: HealthMonitor) {
+    let interval = Duration::from_secs(monitor.poll_interval_secs.max(1));
+    info!(
+        "health monitor started: {} watch(es) every {:?}",
+        monitor.watches.len(),
+        interval
+    );
+    loop {
+        if let Some(breached_path) = poll_once(&monitor) {
+            warn!("health watch '{breached_path}' breached its threshold");
+            apply_fallback(&monitor, &breached_path).await;
+        }
+        tokio::time::sleep(interval).await;
+    }
+}
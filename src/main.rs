@@ -10,20 +10,57 @@
 //
 // You should have received a copy of the GNU General Public License along with this program.  If not, see http://www.gnu.org/licenses/.
 
-use log::info;
+use log::{error, info};
 use std::error::Error;
 use std::future::pending;
-use zbus::connection;
+use zbus::export::futures_util::StreamExt;
+use zbus::{connection, Connection, Proxy};
 mod error;
 
+mod bitstream_verify;
 mod comm;
 
 mod config;
+mod firmware_package;
+mod flash_update;
+mod health_monitor;
 mod platforms;
+#[cfg(feature = "softeners")]
+mod softeners;
 mod system_io;
 
 use crate::comm::dbus::{control_interface::ControlInterface, status_interface::StatusInterface};
 
+/// Watches systemd-logind's `PrepareForSleep` signal and, when the system
+/// resumes (`false` payload), re-applies the last image recorded for every
+/// tracked device so the fabric comes back configured after a suspend/resume
+/// cycle. Returns once the signal stream ends (e.g. when logind is absent).
+async fn watch_resume(conn: &Connection) -> Result<(), Box<dyn Error>> {
+    let proxy = Proxy::new(
+        conn,
+        "org.freedesktop.login1",
+        "/org/freedesktop/login1",
+        "org.freedesktop.login1.Manager",
+    )
+    .await?;
+    let mut signals = proxy.receive_signal("PrepareForSleep").await?;
+    while let Some(signal) = signals.next().await {
+        // The payload is `true` just before sleeping and `false` on resume;
+        // only the resume edge needs the fabric reprogrammed.
+        match signal.body().deserialize::<bool>() {
+            Ok(false) => {
+                info!("resume detected, reprogramming tracked devices");
+                if let Err(e) = platforms::platform::reprogram_on_resume() {
+                    error!("reprogram on resume failed: {e}");
+                }
+            }
+            Ok(true) => {}
+            Err(e) => error!("could not decode PrepareForSleep signal: {e}"),
+        }
+    }
+    Ok(())
+}
+
 #[tokio::main]
 async fn main() -> Result<(), Box<dyn Error>> {
     env_logger::init();
@@ -41,6 +78,30 @@ async fn main() -> Result<(), Box<dyn Error>> {
         .await?;
 
     info!("Started com.canonical.fpgad dbus service");
+
+    // Bring up any FPGA content declared in the `[boot_firmware]` config so a
+    // board configures itself at daemon start without external D-Bus calls.
+    match config::boot_firmware::apply_boot_firmware() {
+        Ok(report) if !report.is_empty() => info!("boot firmware applied:\n{report}"),
+        Ok(_) => info!("no boot firmware configured"),
+        Err(e) => error!("boot firmware application aborted: {e}"),
+    }
+
+    // Start the background health monitor when a `[health_monitor]` section is
+    // configured; otherwise the daemon runs without it.
+    if let Some(monitor) = health_monitor::configured_monitor() {
+        tokio::spawn(health_monitor::run(monitor));
+    }
+
+    // Re-apply recorded images on resume: watch logind's PrepareForSleep signal
+    // and reprogram every tracked device when the system wakes back up.
+    let resume_conn = _conn.clone();
+    tokio::spawn(async move {
+        if let Err(e) = watch_resume(&resume_conn).await {
+            error!("resume watcher exited: {e}");
+        }
+    });
+
     // Do other things or go to wait forever
     pending::<()>().await;
 
@@ -56,6 +56,120 @@ pub static PLATFORM_REGISTRY: OnceLock<Mutex<HashMap<&'static str, PlatformConst
 /// ├── subsystem -> ../../../../../../class/fpga_manager
 /// └── uevent
 ///
+/// The FPGA manager state machine, mirroring the `fpga_mgr_states` enum in
+/// `include/linux/fpga/fpga-mgr.h`. Parsed from the `state` sysfs attribute so
+/// callers can match on a typed value and report programming progress.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FpgaState {
+    Unknown,
+    PowerOff,
+    PowerUp,
+    Reset,
+    FirmwareRequest,
+    FirmwareRequestError,
+    WriteInit,
+    WriteInitError,
+    Write,
+    WriteError,
+    WriteComplete,
+    WriteCompleteError,
+    Operating,
+}
+
+impl FpgaState {
+    /// Parses the kernel state string (e.g. `"write init"`).
+    pub fn parse(state: &str) -> FpgaState {
+        match state.trim() {
+            "power off" => FpgaState::PowerOff,
+            "power up" => FpgaState::PowerUp,
+            "reset" => FpgaState::Reset,
+            "firmware request" => FpgaState::FirmwareRequest,
+            "firmware request error" => FpgaState::FirmwareRequestError,
+            "write init" => FpgaState::WriteInit,
+            "write init error" => FpgaState::WriteInitError,
+            "write" => FpgaState::Write,
+            "write error" => FpgaState::WriteError,
+            "write complete" => FpgaState::WriteComplete,
+            "write complete error" => FpgaState::WriteCompleteError,
+            "operating" => FpgaState::Operating,
+            _ => FpgaState::Unknown,
+        }
+    }
+
+    /// True when the state represents a programming failure.
+    pub fn is_error(self) -> bool {
+        matches!(
+            self,
+            FpgaState::FirmwareRequestError
+                | FpgaState::WriteInitError
+                | FpgaState::WriteError
+                | FpgaState::WriteCompleteError
+        )
+    }
+
+    /// A coarse 0..=100 programming-progress estimate derived from the state,
+    /// for surfacing to clients during a long load.
+    pub fn progress_percent(self) -> u8 {
+        match self {
+            FpgaState::Unknown | FpgaState::PowerOff | FpgaState::PowerUp | FpgaState::Reset => 0,
+            FpgaState::FirmwareRequest => 20,
+            FpgaState::WriteInit => 40,
+            FpgaState::Write => 70,
+            FpgaState::WriteComplete => 90,
+            FpgaState::Operating => 100,
+            _ => 0,
+        }
+    }
+}
+
+/// Describes an image to program onto an FPGA together with the programming
+/// flags it requires. This is the single input to [`Fpga::program`], replacing
+/// the ad-hoc "set flags then load_firmware" dance at every call site.
+///
+/// An image is programmed either from a firmware-relative path (the file path)
+/// or from an in-memory `buffer` (the contiguous-buffer path), mirroring the
+/// kernel's two `fpga_image_info` programming paths.
+#[derive(Debug, Clone, Default)]
+pub struct FpgaImageInfo {
+    /// Firmware-relative path the FPGA manager should load.
+    pub firmware_rel: std::path::PathBuf,
+    /// Raw image bytes to program directly, instead of naming a firmware file.
+    pub buffer: Option<Vec<u8>>,
+    /// Reconfiguration flags to apply before loading (see [`ReconfigFlags`]).
+    pub flags: u32,
+    /// How long to wait for the manager to settle to `operating` after loading.
+    /// `None` skips the wait: `program` returns as soon as the load returns.
+    pub timeout: Option<std::time::Duration>,
+}
+
+impl FpgaImageInfo {
+    pub fn new(firmware_rel: impl Into<std::path::PathBuf>, flags: u32) -> Self {
+        FpgaImageInfo {
+            firmware_rel: firmware_rel.into(),
+            buffer: None,
+            flags,
+            timeout: None,
+        }
+    }
+
+    /// Builds an image that programs `buffer` directly via the contiguous-buffer
+    /// path rather than naming a firmware file.
+    pub fn from_buffer(buffer: Vec<u8>, flags: u32) -> Self {
+        FpgaImageInfo {
+            firmware_rel: std::path::PathBuf::new(),
+            buffer: Some(buffer),
+            flags,
+            timeout: None,
+        }
+    }
+
+    /// Sets the settle timeout and returns the image, for builder-style use.
+    pub fn with_timeout(mut self, timeout: std::time::Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+}
+
 pub trait Fpga {
     #[allow(dead_code)]
     /// get the device handle for this fpga device
@@ -69,22 +183,280 @@ pub trait Fpga {
     #[allow(dead_code)]
     /// Directly load the firmware stored in bitstream_path to the device
     fn load_firmware(&self, bitstream_path_rel: &Path) -> Result<(), FpgadError>;
+
+    /// Programs an image held in memory: `data` is materialized to `name` inside
+    /// the firmware search dir, loaded through [`load_firmware`], and the
+    /// temporary file is removed. This gives callers a buffer-loading entry point
+    /// matching the kernel fpga-mgr move toward a single load path that handles
+    /// buffers as well as named firmware, so upper layers need not assume the
+    /// image lives on disk.
+    fn load_firmware_bytes(&self, name: &str, data: &[u8]) -> Result<(), FpgadError> {
+        let firmware_source_dir = config::system_config::firmware_source_dir()
+            .unwrap_or_else(|_| config::FIRMWARE_SOURCE_DIR.to_string());
+        let temp_abs = std::path::PathBuf::from(&firmware_source_dir).join(name);
+        crate::system_io::fs_write_bytes(&temp_abs, true, data)?;
+        let result = self.load_firmware(std::path::Path::new(name));
+        if let Err(e) = std::fs::remove_file(&temp_abs) {
+            trace!("could not remove temporary buffer {temp_abs:?}: {e}");
+        }
+        result
+    }
+
+    /// The current state parsed into the typed [`FpgaState`] machine.
+    fn state_enum(&self) -> Result<FpgaState, FpgadError> {
+        Ok(FpgaState::parse(&self.state()?))
+    }
+
+    /// Reads the currently-programmed bitstream back from the device, when the
+    /// platform supports it. Defaults to unsupported.
+    fn readback(&self) -> Result<Vec<u8>, FpgadError> {
+        Err(FpgadError::Argument(
+            "bitstream readback is not supported on this platform".into(),
+        ))
+    }
+
+    /// Programs the device from an [`FpgaImageInfo`]: applies the requested
+    /// reconfiguration flags (when non-zero), loads the image via the firmware
+    /// file path or the in-memory buffer path depending on which field is set,
+    /// and waits for the manager to settle to `operating` within the configured
+    /// timeout. Provided as a default so every `Fpga` gets the unified entry
+    /// point for free.
+    fn program(&self, image: &FpgaImageInfo) -> Result<(), FpgadError> {
+        if image.flags != 0 {
+            self.set_flags(ReconfigFlags::validate(image.flags)?)?;
+        }
+        match &image.buffer {
+            Some(buffer) => {
+                let filename = format!("fpgad-buffer-{}.bin", self.device_handle());
+                self.load_firmware_bytes(&filename, buffer)?;
+            }
+            None => self.load_firmware(&image.firmware_rel)?,
+        }
+        if let Some(timeout) = image.timeout {
+            self.wait_until_operating(timeout)?;
+        }
+        record_last_image(self.device_handle(), image.clone());
+        Ok(())
+    }
+
+    /// Polls the manager state until it reaches `operating`, returning a
+    /// structured error if it does not settle (or lands in an error state)
+    /// within `timeout`.
+    fn wait_until_operating(&self, timeout: std::time::Duration) -> Result<(), FpgadError> {
+        let deadline = std::time::Instant::now() + timeout;
+        loop {
+            let state = self.state_enum()?;
+            if state == FpgaState::Operating {
+                return Ok(());
+            }
+            if state.is_error() {
+                return Err(FpgadError::FPGAState(format!(
+                    "{} entered error state {state:?} while programming",
+                    self.device_handle()
+                )));
+            }
+            if std::time::Instant::now() >= deadline {
+                return Err(FpgadError::FPGAState(format!(
+                    "{} did not reach 'operating' within {timeout:?} (last state {state:?})",
+                    self.device_handle()
+                )));
+            }
+            std::thread::sleep(std::time::Duration::from_millis(20));
+        }
+    }
+}
+
+/// The last image successfully programmed onto each device, keyed by device
+/// handle. Used to reprogram the fabric after a suspend/resume cycle, which
+/// leaves the FPGA powered down and unconfigured.
+static LAST_IMAGES: OnceLock<Mutex<HashMap<String, FpgaImageInfo>>> = OnceLock::new();
+
+fn last_images() -> &'static Mutex<HashMap<String, FpgaImageInfo>> {
+    LAST_IMAGES.get_or_init(|| Mutex::new(HashMap::new()))
+}
+
+/// Records the last image programmed onto `device_handle`.
+pub fn record_last_image(device_handle: &str, image: FpgaImageInfo) {
+    if let Ok(mut map) = last_images().lock() {
+        map.insert(device_handle.to_string(), image);
+    }
+}
+
+/// Returns the last image programmed onto `device_handle`, if any.
+pub fn last_image(device_handle: &str) -> Option<FpgaImageInfo> {
+    last_images().lock().ok().and_then(|m| m.get(device_handle).cloned())
+}
+
+/// Re-applies the last image recorded for `device_handle` through the unified
+/// [`Fpga::program`] path (firmware/buffer write plus the settle wait). Used by
+/// the resume hook and the matching D-Bus control method to recover a single
+/// device after a suspend/resume cycle or an unexpected fabric reset. Returns
+/// [`FpgadError::Argument`] when nothing has been programmed onto the device
+/// this session.
+pub fn reprogram_device_on_resume(device_handle: &str) -> Result<(), FpgadError> {
+    let image = last_image(device_handle).ok_or_else(|| {
+        FpgadError::Argument(format!(
+            "no image has been programmed onto {device_handle} to reprogram"
+        ))
+    })?;
+    trace!("reprogramming {device_handle} from its recorded last image");
+    platform_from_compat_or_device("", device_handle)?
+        .fpga(device_handle)?
+        .program(&image)
+}
+
+/// Reprograms every device that has a recorded last image. Intended to be
+/// called on resume from suspend, when the fabric has lost its configuration.
+pub fn reprogram_on_resume() -> Result<(), FpgadError> {
+    let entries: Vec<(String, FpgaImageInfo)> = last_images()
+        .lock()
+        .map_err(|_| FpgadError::Internal("couldn't lock last-image registry".into()))?
+        .iter()
+        .map(|(k, v)| (k.clone(), v.clone()))
+        .collect();
+    for (device_handle, image) in entries {
+        trace!("reprogramming {device_handle} on resume");
+        platform_from_compat_or_device("", &device_handle)?
+            .fpga(&device_handle)?
+            .program(&image)?;
+    }
+    Ok(())
 }
 
 pub trait OverlayHandler {
     /// Applies an overlay to an already existing overlayfs dir,
     /// which may or may not also write a bitstream to an fpga device.
     fn apply_overlay(&self, source_path: &Path) -> Result<(), FpgadError>;
+    /// Applies an overlay directly from an in-memory `.dtbo` blob by writing the
+    /// raw bytes into the `dtbo` configfs file, bypassing the firmware search path.
+    fn apply_overlay_blob(&self, dtbo_bytes: &[u8]) -> Result<(), FpgadError>;
     /// Removes an overlayfs directory from the configfs.
     fn remove_overlay(&self) -> Result<(), FpgadError>;
-    /// Gets the required fpga flags from an overlay file
-    #[allow(dead_code)]
-    fn required_flags(&self) -> Result<isize, FpgadError>;
+    /// Derives the required fpga programming flags from an overlay blob's
+    /// flattened device tree (fpga-region metadata). This is the single
+    /// derivation path; callers strip fpgad-internal sentinel bits with
+    /// [`crate::platforms::universal_components::universal_overlay_handler::strip_internal_flags`]
+    /// before programming.
+    fn required_flags(&self, overlay_blob: &[u8]) -> Result<isize, FpgadError>;
+    /// Reads the `firmware-name` declared by the overlay at the firmware-relative
+    /// `source_path`, so callers can preload the matching bitstream the way the
+    /// kernel `fpga-region` flow does. Returns `None` when the overlay names no
+    /// firmware.
+    fn overlay_firmware_name(&self, source_path: &Path) -> Result<Option<String>, FpgadError>;
     /// gets the overlay application status
     fn status(&self) -> Result<String, FpgadError>;
     fn overlay_fs_path(&self) -> Result<&Path, FpgadError>;
 }
 
+/// Symbolic names for the kernel FPGA-manager reconfiguration flags, so callers
+/// can ask for `"partial,encrypted"` instead of a bare bitmask. The values
+/// mirror the `FPGA_MGR_*` bits in `include/linux/fpga/fpga-mgr.h`.
+pub struct ReconfigFlags;
+
+impl ReconfigFlags {
+    pub const PARTIAL_RECONFIG: u32 = 0x1;
+    pub const EXTERNAL_CONFIG: u32 = 0x2;
+    pub const ENCRYPTED_BITSTREAM: u32 = 0x4;
+    /// Image encrypted with a user-supplied key.
+    pub const ENCRYPTED_USER_KEY: u32 = 0x8;
+    /// Image encrypted with the device's built-in key.
+    pub const ENCRYPTED_DEVICE_KEY: u32 = 0x10;
+
+    /// All bits currently recognised by fpgad.
+    pub const KNOWN: u32 = Self::PARTIAL_RECONFIG
+        | Self::EXTERNAL_CONFIG
+        | Self::ENCRYPTED_BITSTREAM
+        | Self::ENCRYPTED_USER_KEY
+        | Self::ENCRYPTED_DEVICE_KEY;
+
+    /// Parses a comma-separated list of symbolic flag names into a bitmask.
+    /// An empty string yields `0`. Unknown names are rejected.
+    pub fn parse(symbolic: &str) -> Result<u32, FpgadError> {
+        let mut flags = 0u32;
+        for token in symbolic.split(',').map(str::trim).filter(|t| !t.is_empty()) {
+            flags |= match token {
+                "partial" | "partial-reconfig" => Self::PARTIAL_RECONFIG,
+                "external" | "external-config" => Self::EXTERNAL_CONFIG,
+                "encrypted" | "encrypted-bitstream" => Self::ENCRYPTED_BITSTREAM,
+                "encrypted-user-key" => Self::ENCRYPTED_USER_KEY,
+                "encrypted-device-key" => Self::ENCRYPTED_DEVICE_KEY,
+                other => {
+                    return Err(FpgadError::Argument(format!(
+                        "unknown reconfiguration flag '{other}'"
+                    )));
+                }
+            };
+        }
+        Ok(flags)
+    }
+
+    /// Rejects a bitmask that sets bits fpgad does not recognise.
+    pub fn validate(flags: u32) -> Result<u32, FpgadError> {
+        if flags & !Self::KNOWN != 0 {
+            return Err(FpgadError::Argument(format!(
+                "reconfiguration flags {flags:#x} set unknown bits (known: {:#x})",
+                Self::KNOWN
+            )));
+        }
+        Ok(flags)
+    }
+}
+
+pub trait Bridge {
+    /// get the bridge handle for this fpga bridge
+    #[allow(dead_code)]
+    fn bridge_handle(&self) -> &str;
+    /// true when the bridge is currently enabled (passing traffic)
+    fn is_enabled(&self) -> Result<bool, FpgadError>;
+    /// enable (true) or disable (false) the bridge
+    fn set_enabled(&self, enabled: bool) -> Result<(), FpgadError>;
+
+    /// Enables the bridge, reconnecting the fabric to the bus. Spelled to match
+    /// the barebox `fpga_bridge_enable` sequence used around a reconfiguration.
+    #[allow(dead_code)]
+    fn enable(&self) -> Result<(), FpgadError> {
+        self.set_enabled(true)
+    }
+    /// Disables the bridge, isolating the fabric before a reconfiguration.
+    #[allow(dead_code)]
+    fn disable(&self) -> Result<(), FpgadError> {
+        self.set_enabled(false)
+    }
+    /// The bridge `state` as the kernel spells it (`enabled` / `disabled`).
+    #[allow(dead_code)]
+    fn state(&self) -> Result<&'static str, FpgadError> {
+        Ok(if self.is_enabled()? {
+            "enabled"
+        } else {
+            "disabled"
+        })
+    }
+}
+
+/// The bridges associated with each device handle, configured per device so the
+/// load flow knows which bridges to decouple around a reconfiguration.
+static BRIDGE_ASSOCIATIONS: OnceLock<Mutex<HashMap<String, Vec<String>>>> = OnceLock::new();
+
+fn bridge_associations() -> &'static Mutex<HashMap<String, Vec<String>>> {
+    BRIDGE_ASSOCIATIONS.get_or_init(|| Mutex::new(HashMap::new()))
+}
+
+/// Associates `bridges` with `device_handle` for bridge gating.
+pub fn configure_device_bridges(device_handle: &str, bridges: Vec<String>) {
+    if let Ok(mut map) = bridge_associations().lock() {
+        map.insert(device_handle.to_string(), bridges);
+    }
+}
+
+/// Returns the bridges associated with `device_handle`, if any were configured.
+pub fn device_bridges(device_handle: &str) -> Vec<String> {
+    bridge_associations()
+        .lock()
+        .ok()
+        .and_then(|m| m.get(device_handle).cloned())
+        .unwrap_or_default()
+}
+
 pub trait Platform {
     #[allow(dead_code)]
     /// gets the name of the Platform type e.g. Universal or ZynqMP
@@ -93,6 +465,29 @@ pub trait Platform {
     fn fpga(&self, device_handle: &str) -> Result<&dyn Fpga, FpgadError>;
     /// creates and inits an OverlayHandler if not present otherwise gets the instance
     fn overlay_handler(&self, overlay_handle: &str) -> Result<&(dyn OverlayHandler), FpgadError>;
+
+    /// The FPGA bridges that span `region_handle` and must be decoupled around a
+    /// reconfiguration of it. The default resolves the bridges declared by the
+    /// region in the device tree, falling back to every bridge present under the
+    /// bridge class when the region declares none, so a platform that does not
+    /// model regions still isolates the fabric. Mirrors the barebox
+    /// fpga-region→bridge binding.
+    #[allow(dead_code)]
+    fn bridges(&self, region_handle: &str) -> Result<Vec<Box<dyn Bridge>>, FpgadError> {
+        use crate::platforms::universal_components::universal_bridge::UniversalBridge;
+        let handles = crate::platforms::universal_components::fpga_region::region_bridges(
+            region_handle,
+        )?;
+        let handles = if handles.is_empty() {
+            list_fpga_bridges()?
+        } else {
+            handles
+        };
+        Ok(handles
+            .into_iter()
+            .map(|h| Box::new(UniversalBridge::new(&h)) as Box<dyn Bridge>)
+            .collect())
+    }
 }
 
 fn match_platform_string(platform_string: &str) -> Result<Box<dyn Platform>, FpgadError> {
@@ -177,3 +572,40 @@ pub fn register_platform(compatible: &'static str, constructor: PlatformConstruc
 pub fn list_fpga_managers() -> Result<Vec<String>, FpgadError> {
     fs_read_dir(config::FPGA_MANAGERS_DIR.as_ref())
 }
+
+/// Scans /sys/class/fpga_bridge/ for all present bridge nodes and returns a Vec of their handles
+pub fn list_fpga_bridges() -> Result<Vec<String>, FpgadError> {
+    fs_read_dir(config::FPGA_BRIDGES_DIR.as_ref())
+}
+
+/// A device-tree `fpga-region` bound to the manager it owns and the bridges it
+/// isolates, recovered from sysfs so clients can target overlays/bitstreams by
+/// region rather than hard-knowing a manager handle like `fpga0`.
+#[derive(Debug, Clone)]
+pub struct RegionInfo {
+    /// The region's sysfs handle under [`config::FPGA_REGIONS_DIR`].
+    pub region_handle: String,
+    /// The fpga_manager handle resolved through the region's `fpga-mgr` phandle,
+    /// or `None` when the phandle cannot be mapped to a present manager.
+    pub manager_handle: Option<String>,
+    /// The bridge handles the region declares via its `fpga-bridges` phandles.
+    pub bridges: Vec<String>,
+}
+
+/// Walks the FPGA regions present on the system and binds each to its manager
+/// and bridges, recreating the kernel/barebox fpga-region topology that fpgad
+/// otherwise leaves to the user. Regions whose `fpga-mgr` phandle resolves to no
+/// present manager are still reported, with `manager_handle` set to `None`.
+pub fn list_fpga_regions() -> Result<Vec<RegionInfo>, FpgadError> {
+    use crate::platforms::universal_components::fpga_region::{self, Region, UniversalRegion};
+    let mut regions = Vec::new();
+    for region_handle in fpga_region::list_regions()? {
+        let region = UniversalRegion::new(&region_handle);
+        regions.push(RegionInfo {
+            manager_handle: region.manager()?,
+            bridges: fpga_region::region_bridges(&region_handle)?,
+            region_handle,
+        });
+    }
+    Ok(regions)
+}
@@ -0,0 +1,96 @@
+// This file is part of fpgad, an application to manage FPGA subsystem together with device-tree and kernel modules.
+//
+// Copyright 2025 Canonical Ltd.
+//
+// SPDX-License-Identifier: GPL-3.0-only
+//
+// fpgad is free software: you can redistribute it and/or modify it under the terms of the GNU General Public License version 3, as published by the Free Software Foundation.
+//
+// fpgad is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY; without even the implied warranties of MERCHANTABILITY, SATISFACTORY QUALITY, or FITNESS FOR A PARTICULAR PURPOSE.  See the GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License along with this program.  If not, see http://www.gnu.org/licenses/.
+
+//! Chained loading of several bitstreams from one concatenated image set.
+//!
+//! Multi-FPGA boards are often shipped as a single file containing one image
+//! per device, each segment prefixed by its big-endian `u64` length. This
+//! module splits such a set into its segments; programming them is left to the
+//! caller.
+
+use crate::error::FpgadError;
+
+/// Splits a concatenated image set into its constituent segments. Each segment
+/// is stored as an 8-byte big-endian length followed by that many payload bytes.
+pub fn split_segments(buffer: &[u8]) -> Result<Vec<Vec<u8>>, FpgadError> {
+    let mut segments = Vec::new();
+    let mut pos = 0usize;
+    while pos < buffer.len() {
+        let len_bytes = buffer.get(pos..pos + 8).ok_or_else(|| {
+            FpgadError::Argument("concatenated image set truncated in length header".into())
+        })?;
+        let len = u64::from_be_bytes(len_bytes.try_into().unwrap()) as usize;
+        pos += 8;
+        let end = pos.checked_add(len).ok_or_else(|| {
+            FpgadError::Argument("concatenated image set segment length overflows".into())
+        })?;
+        let payload = buffer.get(pos..end).ok_or_else(|| {
+            FpgadError::Argument(format!(
+                "concatenated image set declares a {len}-byte segment but only {} bytes remain",
+                buffer.len() - pos
+            ))
+        })?;
+        segments.push(payload.to_vec());
+        pos += len;
+    }
+    Ok(segments)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn segment(payload: &[u8]) -> Vec<u8> {
+        let mut buf = (payload.len() as u64).to_be_bytes().to_vec();
+        buf.extend_from_slice(payload);
+        buf
+    }
+
+    #[test]
+    fn split_segments_round_trips_multiple_images() {
+        let mut buffer = segment(b"first-image");
+        buffer.extend(segment(b"second-image"));
+
+        let segments = split_segments(&buffer).unwrap();
+
+        assert_eq!(segments, vec![b"first-image".to_vec(), b"second-image".to_vec()]);
+    }
+
+    #[test]
+    fn split_segments_rejects_truncated_length_header() {
+        let buffer = vec![0u8; 4];
+
+        let err = split_segments(&buffer).unwrap_err();
+
+        assert!(err.to_string().contains("truncated"));
+    }
+
+    #[test]
+    fn split_segments_rejects_declared_length_past_end_of_buffer() {
+        let mut buffer = 100u64.to_be_bytes().to_vec();
+        buffer.extend_from_slice(b"too-short");
+
+        let err = split_segments(&buffer).unwrap_err();
+
+        assert!(err.to_string().contains("bytes remain"));
+    }
+
+    #[test]
+    fn split_segments_rejects_length_that_overflows_usize() {
+        let mut buffer = u64::MAX.to_be_bytes().to_vec();
+        buffer.extend_from_slice(b"payload");
+
+        let err = split_segments(&buffer).unwrap_err();
+
+        assert!(err.to_string().contains("overflows"));
+    }
+}
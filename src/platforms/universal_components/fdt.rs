@@ -0,0 +1,240 @@
+// This file is part of fpgad, an application to manage FPGA subsystem together with device-tree and kernel modules.
+//
+// Copyright 2025 Canonical Ltd.
+//
+// SPDX-License-Identifier: GPL-3.0-only
+//
+// fpgad is free software: you can redistribute it and/or modify it under the terms of the GNU General Public License version 3, as published by the Free Software Foundation.
+//
+// fpgad is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY; without even the implied warranties of MERCHANTABILITY, SATISFACTORY QUALITY, or FITNESS FOR A PARTICULAR PURPOSE.  See the GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License along with this program.  If not, see http://www.gnu.org/licenses/.
+
+//! A small, read-only parser for the flattened device-tree (FDT) struct block.
+//!
+//! Only the subset needed to inspect overlay fragments for `fpga-region`
+//! metadata is implemented: it walks the node/property token stream and lets
+//! callers look up properties on nodes whose `compatible` matches.
+
+use crate::error::FpgadError;
+
+const FDT_MAGIC: u32 = 0xd00d_feed;
+const FDT_BEGIN_NODE: u32 = 0x1;
+const FDT_END_NODE: u32 = 0x2;
+const FDT_PROP: u32 = 0x3;
+const FDT_NOP: u32 = 0x4;
+const FDT_END: u32 = 0x9;
+
+/// A single property parsed out of the struct block.
+#[derive(Debug, Clone)]
+pub struct Property {
+    pub name: String,
+    pub value: Vec<u8>,
+}
+
+impl Property {
+    /// Interpret the value as a null-terminated string.
+    pub fn as_str(&self) -> Option<String> {
+        let trimmed = self.value.split(|b| *b == 0).next().unwrap_or(&[]);
+        std::str::from_utf8(trimmed).ok().map(|s| s.to_string())
+    }
+
+    /// True for an empty (boolean) property, i.e. one that is simply present.
+    pub fn is_bool_true(&self) -> bool {
+        self.value.is_empty()
+    }
+
+    /// Interpret the value as a big-endian u32 cell.
+    pub fn as_u32(&self) -> Option<u32> {
+        self.value
+            .get(..4)
+            .map(|b| u32::from_be_bytes([b[0], b[1], b[2], b[3]]))
+    }
+
+    /// Interpret the value as a list of big-endian u32 cells, as used by
+    /// phandle-list properties like `fpga-bridges`. A trailing partial cell is
+    /// ignored.
+    pub fn as_u32_list(&self) -> Vec<u32> {
+        self.value
+            .chunks_exact(4)
+            .map(|b| u32::from_be_bytes([b[0], b[1], b[2], b[3]]))
+            .collect()
+    }
+}
+
+/// A node parsed out of the struct block together with its direct properties.
+#[derive(Debug, Clone)]
+pub struct Node {
+    pub name: String,
+    pub properties: Vec<Property>,
+}
+
+impl Node {
+    pub fn property(&self, name: &str) -> Option<&Property> {
+        self.properties.iter().find(|p| p.name == name)
+    }
+
+    /// True if this node's `compatible` list contains `needle`.
+    pub fn compatible_contains(&self, needle: &str) -> bool {
+        self.property("compatible").is_some_and(|p| {
+            p.value
+                .split(|b| *b == 0)
+                .filter_map(|s| std::str::from_utf8(s).ok())
+                .any(|s| s.contains(needle))
+        })
+    }
+}
+
+/// Returns the `firmware-name` declared by the first fpga-region node in the
+/// overlay, if any. This is the bitstream the overlay expects the FPGA manager
+/// to load.
+pub fn firmware_name(blob: &[u8]) -> Result<Option<String>, FpgadError> {
+    for node in parse_nodes(blob)? {
+        if node.compatible_contains("fpga-region") {
+            if let Some(name) = node.property("firmware-name").and_then(|p| p.as_str()) {
+                return Ok(Some(name));
+            }
+        }
+    }
+    Ok(None)
+}
+
+/// Returns the phandle referenced by the first fpga-region node's `fpga-mgr`
+/// property, i.e. the manager the region expects to load its bitstream.
+pub fn fpga_mgr_phandle(blob: &[u8]) -> Result<Option<u32>, FpgadError> {
+    for node in parse_nodes(blob)? {
+        if node.compatible_contains("fpga-region") {
+            if let Some(phandle) = node.property("fpga-mgr").and_then(|p| p.as_u32()) {
+                return Ok(Some(phandle));
+            }
+        }
+    }
+    Ok(None)
+}
+
+/// Returns the `fpga-mgr` phandle referenced anywhere in the overlay, not just
+/// on an fpga-region node. Real overlays sometimes carry the `fpga-mgr` phandle
+/// on a parent of the region node, so when no fpga-region node declares one this
+/// falls back to the first node that does — mirroring the barebox resolver that
+/// walks parents upward to find the responsible manager.
+pub fn fpga_mgr_phandle_anywhere(blob: &[u8]) -> Result<Option<u32>, FpgadError> {
+    if let Some(phandle) = fpga_mgr_phandle(blob)? {
+        return Ok(Some(phandle));
+    }
+    for node in parse_nodes(blob)? {
+        if let Some(phandle) = node.property("fpga-mgr").and_then(|p| p.as_u32()) {
+            return Ok(Some(phandle));
+        }
+    }
+    Ok(None)
+}
+
+/// Returns the phandle list referenced by the first fpga-region node's
+/// `fpga-bridges` property, i.e. the bridges that isolate the region during
+/// reconfiguration. Empty when no region declares any.
+pub fn fpga_bridges_phandles(blob: &[u8]) -> Result<Vec<u32>, FpgadError> {
+    for node in parse_nodes(blob)? {
+        if node.compatible_contains("fpga-region") {
+            if let Some(prop) = node.property("fpga-bridges") {
+                return Ok(prop.as_u32_list());
+            }
+        }
+    }
+    Ok(Vec::new())
+}
+
+/// Returns the name of the node that declares `phandle` via its `phandle` or
+/// legacy `linux,phandle` property, used to resolve an `fpga-mgr` reference back
+/// to a manager node.
+pub fn node_name_for_phandle(blob: &[u8], phandle: u32) -> Result<Option<String>, FpgadError> {
+    for node in parse_nodes(blob)? {
+        let declared = node
+            .property("phandle")
+            .or_else(|| node.property("linux,phandle"))
+            .and_then(|p| p.as_u32());
+        if declared == Some(phandle) {
+            return Ok(Some(node.name.clone()));
+        }
+    }
+    Ok(None)
+}
+
+fn read_u32(blob: &[u8], off: usize) -> Result<u32, FpgadError> {
+    blob.get(off..off + 4)
+        .map(|b| u32::from_be_bytes([b[0], b[1], b[2], b[3]]))
+        .ok_or_else(|| FpgadError::OverlayStatus("FDT truncated while reading cell".into()))
+}
+
+fn read_cstr(blob: &[u8], off: usize) -> Result<String, FpgadError> {
+    let rest = blob
+        .get(off..)
+        .ok_or_else(|| FpgadError::OverlayStatus("FDT string offset out of bounds".into()))?;
+    let end = rest
+        .iter()
+        .position(|b| *b == 0)
+        .ok_or_else(|| FpgadError::OverlayStatus("FDT string not null-terminated".into()))?;
+    std::str::from_utf8(&rest[..end])
+        .map(|s| s.to_string())
+        .map_err(|_| FpgadError::OverlayStatus("FDT string is not valid UTF-8".into()))
+}
+
+/// Parses the flattened device tree and returns every node in the struct block.
+pub fn parse_nodes(blob: &[u8]) -> Result<Vec<Node>, FpgadError> {
+    if read_u32(blob, 0)? != FDT_MAGIC {
+        return Err(FpgadError::OverlayStatus(
+            "overlay blob does not start with the FDT magic".into(),
+        ));
+    }
+    let off_struct = read_u32(blob, 8)? as usize;
+    let off_strings = read_u32(blob, 12)? as usize;
+
+    let mut nodes = Vec::new();
+    // Indices into `nodes` for the chain of currently-open nodes, so a property
+    // is attributed to the node that is actually open rather than to whichever
+    // node was pushed last (which would misfile properties that follow a child
+    // subnode against that child).
+    let mut open: Vec<usize> = Vec::new();
+    let mut pos = off_struct;
+    loop {
+        let token = read_u32(blob, pos)?;
+        pos += 4;
+        match token {
+            FDT_BEGIN_NODE => {
+                let name = read_cstr(blob, pos)?;
+                pos += name.len() + 1;
+                pos = (pos + 3) & !3;
+                open.push(nodes.len());
+                nodes.push(Node {
+                    name,
+                    properties: Vec::new(),
+                });
+            }
+            FDT_PROP => {
+                let len = read_u32(blob, pos)? as usize;
+                let name_off = read_u32(blob, pos + 4)? as usize;
+                pos += 8;
+                let value = blob
+                    .get(pos..pos + len)
+                    .ok_or_else(|| FpgadError::OverlayStatus("FDT property truncated".into()))?
+                    .to_vec();
+                pos += len;
+                pos = (pos + 3) & !3;
+                let name = read_cstr(blob, off_strings + name_off)?;
+                if let Some(&idx) = open.last() {
+                    nodes[idx].properties.push(Property { name, value });
+                }
+            }
+            FDT_END_NODE => {
+                open.pop();
+            }
+            FDT_NOP => {}
+            FDT_END => break,
+            other => {
+                return Err(FpgadError::OverlayStatus(format!(
+                    "unexpected FDT token {other:#x}"
+                )));
+            }
+        }
+    }
+    Ok(nodes)
+}
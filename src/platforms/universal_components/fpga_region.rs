@@ -0,0 +1,263 @@
+// This file is part of fpgad, an application to manage FPGA subsystem together with device-tree and kernel modules.
+//
+// Copyright 2025 Canonical Ltd.
+//
+// SPDX-License-Identifier: GPL-3.0-only
+//
+// fpgad is free software: you can redistribute it and/or modify it under the terms of the GNU General Public License version 3, as published by the Free Software Foundation.
+//
+// fpgad is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY; without even the implied warranties of MERCHANTABILITY, SATISFACTORY QUALITY, or FITNESS FOR A PARTICULAR PURPOSE.  See the GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License along with this program.  If not, see http://www.gnu.org/licenses/.
+
+//! First-class FPGA "region" programming.
+//!
+//! A region ties together an FPGA manager, the bridges that isolate it and an
+//! optional device-tree overlay, and programs them as one atomic operation:
+//! the bridges are disabled, the flags are set, the overlay is applied, and the
+//! bridges are re-enabled. If any step fails the overlay is removed and the
+//! bridges are brought back up (via [`BridgeGate`]'s drop).
+
+use crate::config;
+use crate::error::FpgadError;
+use crate::platforms::platform::{Fpga, OverlayHandler, list_fpga_managers};
+use crate::platforms::universal_components::universal_bridge::BridgeGate;
+use crate::platforms::universal_components::universal_overlay_handler::UniversalOverlayHandler;
+use crate::system_io::{fs_read_bytes, fs_read_dir};
+use log::{info, trace};
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, OnceLock};
+
+/// Everything needed to program one FPGA region.
+pub struct RegionRequest<'a> {
+    /// Bridges to disable for the duration of the reconfiguration.
+    pub bridges: &'a [&'a str],
+    /// Programming flags to set on the manager before applying the overlay.
+    pub flags: u32,
+    /// The overlay handle to apply under.
+    pub overlay_handle: &'a str,
+    /// Firmware-relative path to the overlay source.
+    pub overlay_source_rel: &'a Path,
+}
+
+/// A logical FPGA region: a device-tree `fpga-region` node that owns an FPGA
+/// manager (referenced by its `fpga-mgr` phandle) and the set of overlays that
+/// have been applied under it.
+pub trait Region {
+    /// The region's sysfs node name (its handle under [`config::FPGA_REGIONS_DIR`]).
+    fn region_handle(&self) -> &str;
+    /// The device handle of the fpga_manager this region owns, resolved through
+    /// the region's `fpga-mgr` phandle. `None` when the phandle cannot be matched.
+    fn manager(&self) -> Result<Option<String>, FpgadError>;
+    /// The overlay handles fpgad has applied under this region.
+    fn overlays(&self) -> Result<Vec<String>, FpgadError>;
+}
+
+/// Sysfs-backed [`Region`] rooted at `/sys/class/fpga_region/<handle>`.
+pub struct UniversalRegion {
+    region_handle: String,
+}
+
+impl UniversalRegion {
+    pub fn new(region_handle: &str) -> Self {
+        UniversalRegion {
+            region_handle: region_handle.to_string(),
+        }
+    }
+}
+
+impl Region for UniversalRegion {
+    fn region_handle(&self) -> &str {
+        &self.region_handle
+    }
+
+    fn manager(&self) -> Result<Option<String>, FpgadError> {
+        let phandle = match read_phandle(
+            &PathBuf::from(config::FPGA_REGIONS_DIR)
+                .join(&self.region_handle)
+                .join("of_node/fpga-mgr"),
+        )? {
+            Some(p) => p,
+            None => return Ok(None),
+        };
+        for manager in list_fpga_managers()? {
+            let mgr_phandle = read_phandle(
+                &PathBuf::from(config::FPGA_MANAGERS_DIR)
+                    .join(&manager)
+                    .join("of_node/phandle"),
+            )?;
+            if mgr_phandle == Some(phandle) {
+                return Ok(Some(manager));
+            }
+        }
+        Ok(None)
+    }
+
+    fn overlays(&self) -> Result<Vec<String>, FpgadError> {
+        Ok(region_overlays(&self.region_handle))
+    }
+}
+
+/// Reads a device-tree phandle cell (a big-endian u32) from a `/proc`-style
+/// of_node property file, returning `None` when the file is absent or too short.
+fn read_phandle(path: &Path) -> Result<Option<u32>, FpgadError> {
+    match fs_read_bytes(path) {
+        Ok(bytes) => Ok(bytes
+            .get(..4)
+            .map(|b| u32::from_be_bytes([b[0], b[1], b[2], b[3]]))),
+        Err(_) => Ok(None),
+    }
+}
+
+/// Lists the FPGA regions present on the system by their sysfs handle.
+pub fn list_regions() -> Result<Vec<String>, FpgadError> {
+    fs_read_dir(config::FPGA_REGIONS_DIR.as_ref())
+}
+
+/// Resolves the bridges that `region_handle` declares via its `fpga-bridges`
+/// phandle list, mapping each phandle to the bridge under
+/// [`config::FPGA_BRIDGES_DIR`] that declares it. Returns an empty vector when
+/// the region declares no bridges (or the sysfs node is absent), so callers can
+/// fall back to a broader set.
+pub fn region_bridges(region_handle: &str) -> Result<Vec<String>, FpgadError> {
+    let phandles = read_phandle_list(
+        &PathBuf::from(config::FPGA_REGIONS_DIR)
+            .join(region_handle)
+            .join("of_node/fpga-bridges"),
+    );
+    if phandles.is_empty() {
+        return Ok(Vec::new());
+    }
+    let mut bridges = Vec::new();
+    for bridge in fs_read_dir(config::FPGA_BRIDGES_DIR.as_ref())? {
+        let bridge_phandle = read_phandle(
+            &PathBuf::from(config::FPGA_BRIDGES_DIR)
+                .join(&bridge)
+                .join("of_node/phandle"),
+        )?;
+        if bridge_phandle.is_some_and(|p| phandles.contains(&p)) {
+            bridges.push(bridge);
+        }
+    }
+    Ok(bridges)
+}
+
+/// Reads a `/proc`-style property file holding a list of big-endian u32 phandle
+/// cells, returning them in order. An absent or unreadable file yields an empty
+/// list rather than an error, matching [`read_phandle`].
+fn read_phandle_list(path: &Path) -> Vec<u32> {
+    match fs_read_bytes(path) {
+        Ok(bytes) => bytes
+            .chunks_exact(4)
+            .map(|b| u32::from_be_bytes([b[0], b[1], b[2], b[3]]))
+            .collect(),
+        Err(_) => Vec::new(),
+    }
+}
+
+/// The overlays fpgad has applied under each region, keyed by region handle.
+/// Mirrors the `LAST_IMAGES` registry pattern: configfs does not model the
+/// region→overlay relationship, so fpgad records it as overlays are applied.
+static REGION_OVERLAYS: OnceLock<Mutex<HashMap<String, HashSet<String>>>> = OnceLock::new();
+
+fn region_overlays_map() -> &'static Mutex<HashMap<String, HashSet<String>>> {
+    REGION_OVERLAYS.get_or_init(|| Mutex::new(HashMap::new()))
+}
+
+/// Records that `overlay_handle` has been applied under `region_handle`.
+pub fn record_region_overlay(region_handle: &str, overlay_handle: &str) {
+    if let Ok(mut map) = region_overlays_map().lock() {
+        map.entry(region_handle.to_string())
+            .or_default()
+            .insert(overlay_handle.to_string());
+    }
+}
+
+/// The overlay handles recorded against `region_handle`.
+pub fn region_overlays(region_handle: &str) -> Vec<String> {
+    region_overlays_map()
+        .lock()
+        .ok()
+        .and_then(|m| m.get(region_handle).map(|s| s.iter().cloned().collect()))
+        .unwrap_or_default()
+}
+
+/// Resolves the region that owns `device_handle` by matching each region's
+/// `fpga-mgr` phandle back to the manager. `None` when no region claims it.
+pub fn region_for_manager(device_handle: &str) -> Result<Option<String>, FpgadError> {
+    for region in list_regions()? {
+        if UniversalRegion::new(&region).manager()?.as_deref() == Some(device_handle) {
+            return Ok(Some(region));
+        }
+    }
+    Ok(None)
+}
+
+/// Validates that `overlay_handle` is legal for the region that owns
+/// `device_handle`: if the manager belongs to a region, the overlay must have
+/// been recorded against that region. Regions with no recorded overlays yet and
+/// managers that belong to no region are permitted.
+pub fn validate_overlay_for_manager(
+    device_handle: &str,
+    overlay_handle: &str,
+) -> Result<(), FpgadError> {
+    let region = match region_for_manager(device_handle)? {
+        Some(r) => r,
+        None => return Ok(()),
+    };
+    let overlays = region_overlays(&region);
+    if overlays.is_empty() || overlays.iter().any(|o| o == overlay_handle) {
+        return Ok(());
+    }
+    Err(FpgadError::Argument(format!(
+        "overlay '{overlay_handle}' does not belong to region '{region}' which owns {device_handle}"
+    )))
+}
+
+/// Programs a region atomically: bridges down, flags set, overlay applied,
+/// bridges back up. On failure the overlay is removed before the error is
+/// propagated, and the bridges are always restored.
+pub fn program_region(fpga: &dyn Fpga, request: &RegionRequest) -> Result<(), FpgadError> {
+    validate_overlay_for_manager(fpga.device_handle(), request.overlay_handle)?;
+    let _gate = BridgeGate::close(request.bridges)?;
+    trace!("bridges closed for region programming");
+
+    if request.flags != 0 {
+        fpga.set_flags(request.flags)?;
+    }
+
+    let overlay_handler = UniversalOverlayHandler::new(request.overlay_handle);
+    match overlay_handler.apply_overlay(&request.overlay_source_rel.to_string_lossy()) {
+        Ok(()) => {
+            info!(
+                "region programmed: overlay '{}' applied to {}",
+                request.overlay_handle,
+                fpga.device_handle()
+            );
+            if let Ok(Some(region)) = region_for_manager(fpga.device_handle()) {
+                record_region_overlay(&region, request.overlay_handle);
+            }
+            Ok(())
+        }
+        Err(e) => {
+            trace!("region programming failed ({e}); removing overlay");
+            let _ = overlay_handler.remove_overlay();
+            Err(e)
+        }
+    }
+    // `_gate` drops here, re-enabling the bridges.
+}
+
+/// Tears a region down: disables the bridges, removes the overlay (which unloads
+/// the bitstream), then re-enables the bridges. Used to remove a programmed
+/// bitstream by undoing the region that carried it.
+pub fn teardown_region(bridges: &[&str], overlay_handle: &str) -> Result<(), FpgadError> {
+    let _gate = BridgeGate::close(bridges)?;
+    trace!("bridges closed for region teardown");
+    let overlay_handler = UniversalOverlayHandler::new(overlay_handle);
+    overlay_handler.remove_overlay()?;
+    info!("region torn down: overlay '{overlay_handle}' removed");
+    Ok(())
+    // `_gate` drops here, re-enabling the bridges.
+}
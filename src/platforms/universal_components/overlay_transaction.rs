@@ -0,0 +1,117 @@
+// This file is part of fpgad, an application to manage FPGA subsystem together with device-tree and kernel modules.
+//
+// Copyright 2025 Canonical Ltd.
+//
+// SPDX-License-Identifier: GPL-3.0-only
+//
+// fpgad is free software: you can redistribute it and/or modify it under the terms of the GNU General Public License version 3, as published by the Free Software Foundation.
+//
+// fpgad is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY; without even the implied warranties of MERCHANTABILITY, SATISFACTORY QUALITY, or FITNESS FOR A PARTICULAR PURPOSE.  See the GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License along with this program.  If not, see http://www.gnu.org/licenses/.
+
+//! Transactional application of a set of device-tree overlays.
+//!
+//! Overlays in a set often depend on each other, so a partial apply leaves the
+//! system in an inconsistent state. [`OverlayTransaction`] applies overlays one
+//! by one and, if any apply fails, automatically removes every overlay it has
+//! already applied (in reverse order) before returning the original error.
+
+use crate::error::FpgadError;
+use crate::platforms::platform::OverlayHandler;
+use crate::platforms::universal_components::universal_overlay_handler::UniversalOverlayHandler;
+use log::{trace, warn};
+use std::path::Path;
+
+/// A single overlay to be applied as part of a transaction.
+pub struct OverlaySpec<'a> {
+    pub overlay_handle: &'a str,
+    pub source_path_rel: &'a Path,
+}
+
+/// The result of applying one overlay in a transaction.
+pub struct OverlayOutcome {
+    pub overlay_handle: String,
+    pub status: String,
+}
+
+/// The result of a whole transaction: the per-overlay outcomes in apply order
+/// and the deduplicated set of `firmware-name` references collected across the
+/// set (in first-seen order) so the caller loads each bitstream only once.
+pub struct TransactionReport {
+    pub outcomes: Vec<OverlayOutcome>,
+    pub firmwares: Vec<String>,
+}
+
+/// Tracks overlays applied so far so they can be rolled back on failure.
+#[derive(Default)]
+pub struct OverlayTransaction {
+    applied: Vec<UniversalOverlayHandler>,
+}
+
+impl OverlayTransaction {
+    pub fn new() -> Self {
+        OverlayTransaction::default()
+    }
+
+    /// Applies every overlay in `specs` in order. Firmware loading is deferred:
+    /// the overlays are staged first, then any bitstream they reference is loaded
+    /// by the caller after `commit`. The `firmware-name` declared by each overlay
+    /// is collected into a single deduplicated list (first-seen order) so the
+    /// same bitstream is not loaded twice in one transaction. On the first
+    /// failure, all overlays applied so far are removed and the triggering error
+    /// is returned.
+    pub fn apply_all(&mut self, specs: &[OverlaySpec]) -> Result<TransactionReport, FpgadError> {
+        let mut outcomes = Vec::with_capacity(specs.len());
+        let mut firmwares: Vec<String> = Vec::new();
+        for spec in specs {
+            let handler = UniversalOverlayHandler::new(spec.overlay_handle);
+            let source_rel = spec.source_path_rel.to_string_lossy();
+            match handler.apply_overlay(&source_rel) {
+                Ok(()) => {
+                    trace!("applied overlay '{}'", spec.overlay_handle);
+                    // Collect the overlay's firmware-name reference, deduplicating
+                    // against what earlier overlays in the set already named.
+                    if let Ok(Some(name)) = handler.overlay_firmware_name(spec.source_path_rel) {
+                        if !firmwares.contains(&name) {
+                            firmwares.push(name);
+                        }
+                    }
+                    outcomes.push(OverlayOutcome {
+                        overlay_handle: spec.overlay_handle.to_string(),
+                        status: format!("applied from {source_rel}"),
+                    });
+                    self.applied.push(handler);
+                }
+                Err(e) => {
+                    warn!(
+                        "applying overlay '{}' failed ({e}); rolling back {} overlay(s)",
+                        spec.overlay_handle,
+                        self.applied.len()
+                    );
+                    self.rollback();
+                    return Err(e);
+                }
+            }
+        }
+        Ok(TransactionReport {
+            outcomes,
+            firmwares,
+        })
+    }
+
+    /// Removes every applied overlay in reverse order. Individual removal errors
+    /// are logged but do not stop the rollback.
+    fn rollback(&mut self) {
+        while let Some(handler) = self.applied.pop() {
+            if let Err(e) = handler.remove_overlay() {
+                warn!("failed to roll back an overlay: {e}");
+            }
+        }
+    }
+
+    /// Consumes the transaction, leaving the applied overlays in place.
+    pub fn commit(self) -> Vec<UniversalOverlayHandler> {
+        self.applied
+    }
+}
@@ -0,0 +1,155 @@
+// This file is part of fpgad, an application to manage FPGA subsystem together with device-tree and kernel modules.
+//
+// Copyright 2025 Canonical Ltd.
+//
+// SPDX-License-Identifier: GPL-3.0-only
+//
+// fpgad is free software: you can redistribute it and/or modify it under the terms of the GNU General Public License version 3, as published by the Free Software Foundation.
+//
+// fpgad is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY; without even the implied warranties of MERCHANTABILITY, SATISFACTORY QUALITY, or FITNESS FOR A PARTICULAR PURPOSE.  See the GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License along with this program.  If not, see http://www.gnu.org/licenses/.
+
+//! FPGA bridge control.
+//!
+//! FPGA bridges isolate the reconfigurable fabric from the rest of the SoC.
+//! They must be disabled before the FPGA is reprogrammed and re-enabled once
+//! the new image is in place, otherwise half-programmed logic can drive the
+//! bus. A bridge is controlled through its `state` file in
+//! `/sys/class/fpga_bridge/<bridge>/` (`enabled` / `disabled`).
+
+use crate::config;
+use crate::error::FpgadError;
+use crate::platforms::platform::Bridge;
+use crate::system_io::{fs_read, fs_write};
+use log::{info, trace};
+use std::path::PathBuf;
+
+#[derive(Debug)]
+pub struct UniversalBridge {
+    bridge_handle: String,
+}
+
+impl UniversalBridge {
+    pub(crate) fn new(bridge_handle: &str) -> UniversalBridge {
+        UniversalBridge {
+            bridge_handle: bridge_handle.to_owned(),
+        }
+    }
+
+    fn state_path(&self) -> PathBuf {
+        PathBuf::from(config::FPGA_BRIDGES_DIR)
+            .join(&self.bridge_handle)
+            .join("state")
+    }
+}
+
+impl Bridge for UniversalBridge {
+    fn bridge_handle(&self) -> &str {
+        &self.bridge_handle
+    }
+
+    /// Reads the bridge `state` file, returning true when it reports `enabled`.
+    fn is_enabled(&self) -> Result<bool, FpgadError> {
+        let state_path = self.state_path();
+        trace!("reading {state_path:?}");
+        Ok(fs_read(&state_path)?.trim() == "enabled")
+    }
+
+    /// Writes `enabled` / `disabled` to the bridge `state` file.
+    fn set_enabled(&self, enabled: bool) -> Result<(), FpgadError> {
+        let state_path = self.state_path();
+        let value = if enabled { "enabled" } else { "disabled" };
+        trace!("writing '{value}' to {state_path:?}");
+        fs_write(&state_path, false, value)?;
+        info!("bridge '{}' set to '{value}'", self.bridge_handle);
+        Ok(())
+    }
+}
+
+/// Disables every named bridge, isolating the fabric before reprogramming. On
+/// the first failure the bridges already disabled are restored to `enabled` and
+/// the error is returned, so a partial disable never leaves the fabric stranded.
+pub fn disable_bridges(handles: &[String]) -> Result<(), FpgadError> {
+    let mut disabled: Vec<&String> = Vec::with_capacity(handles.len());
+    for handle in handles {
+        if let Err(e) = UniversalBridge::new(handle).set_enabled(false) {
+            for done in disabled {
+                if let Err(restore) = UniversalBridge::new(done).set_enabled(true) {
+                    trace!("failed to restore bridge '{done}': {restore}");
+                }
+            }
+            return Err(e);
+        }
+        disabled.push(handle);
+    }
+    Ok(())
+}
+
+/// Re-enables every named bridge after reprogramming, bringing the fabric back
+/// online. Every bridge is attempted; the first error encountered is returned.
+pub fn enable_bridges(handles: &[String]) -> Result<(), FpgadError> {
+    let mut first_error = None;
+    for handle in handles {
+        if let Err(e) = UniversalBridge::new(handle).set_enabled(true) {
+            trace!("failed to enable bridge '{handle}': {e}");
+            first_error.get_or_insert(e);
+        }
+    }
+    match first_error {
+        Some(e) => Err(e),
+        None => Ok(()),
+    }
+}
+
+/// Reads each bridge's current `enabled`/`disabled` state, for surfacing through
+/// the status interface.
+pub fn bridge_states(handles: &[String]) -> Result<Vec<(String, bool)>, FpgadError> {
+    handles
+        .iter()
+        .map(|h| UniversalBridge::new(h).is_enabled().map(|e| (h.clone(), e)))
+        .collect()
+}
+
+/// RAII guard that disables a set of bridges on creation and re-enables them
+/// when dropped, bracketing an FPGA reconfiguration. If reconfiguration panics
+/// or returns early, the bridges are still brought back up.
+pub struct BridgeGate {
+    bridges: Vec<UniversalBridge>,
+}
+
+impl BridgeGate {
+    /// Disables every named bridge and returns a guard that re-enables them on drop.
+    /// On a partial failure the bridges already disabled are restored to
+    /// `enabled` before the error is returned, mirroring [`disable_bridges`] so
+    /// a failed gate never leaves the fabric stranded half-disabled.
+    pub fn close(bridge_handles: &[&str]) -> Result<BridgeGate, FpgadError> {
+        let mut bridges = Vec::with_capacity(bridge_handles.len());
+        for handle in bridge_handles {
+            let bridge = UniversalBridge::new(handle);
+            if let Err(e) = bridge.set_enabled(false) {
+                for done in &bridges {
+                    if let Err(restore) = done.set_enabled(true) {
+                        trace!(
+                            "failed to restore bridge '{}': {restore}",
+                            done.bridge_handle()
+                        );
+                    }
+                }
+                return Err(e);
+            }
+            bridges.push(bridge);
+        }
+        Ok(BridgeGate { bridges })
+    }
+}
+
+impl Drop for BridgeGate {
+    fn drop(&mut self) {
+        for bridge in &self.bridges {
+            if let Err(e) = bridge.set_enabled(true) {
+                trace!("failed to re-enable bridge '{}': {e}", bridge.bridge_handle());
+            }
+        }
+    }
+}
@@ -13,7 +13,7 @@
 use crate::config;
 use crate::error::FpgadError;
 use crate::platforms::platform::Fpga;
-use crate::system_io::{fs_read, fs_write};
+use crate::system_io::{fs_read, fs_read_bytes, fs_write};
 use log::{error, info, trace};
 use std::path::Path;
 
@@ -123,6 +123,16 @@ impl Fpga for UniversalFPGA {
         }
     }
 
+    /// Reads the programmed bitstream back from the manager's `readback` sysfs
+    /// attribute. Only available on devices whose driver exposes readback.
+    fn readback(&self) -> Result<Vec<u8>, FpgadError> {
+        let readback_path = Path::new(config::FPGA_MANAGERS_DIR)
+            .join(self.device_handle())
+            .join("readback");
+        trace!("reading back bitstream from {readback_path:?}");
+        fs_read_bytes(&readback_path)
+    }
+
     /// This can be used to manually load a firmware if the overlay does not trigger the load.
     /// Note: always load firmware before overlay.
     fn load_firmware(&self, bitstream_path_rel: &str) -> Result<(), FpgadError> {
@@ -13,9 +13,108 @@
 use crate::config;
 use crate::error::FpgadError;
 use crate::platforms::platform::OverlayHandler;
-use crate::system_io::{fs_create_dir, fs_read, fs_remove_dir, fs_write};
+use crate::platforms::universal_components::fdt;
+use crate::platforms::universal_components::universal_bridge;
+use crate::system_io::{
+    fs_create_dir, fs_read, fs_read_bytes, fs_remove_dir, fs_write, fs_write_bytes,
+    materialize_if_compressed, maybe_decompress,
+};
 use log::{info, trace};
+use std::collections::HashMap;
 use std::path::{Path, PathBuf};
+use std::sync::{Mutex, OnceLock};
+
+/// The `firmware-name` each applied overlay pulled in, keyed by overlay handle.
+/// configfs does not expose this after the fact, so fpgad records it as overlays
+/// are applied (mirroring the `LAST_IMAGES` registry pattern).
+static OVERLAY_FIRMWARE: OnceLock<Mutex<HashMap<String, String>>> = OnceLock::new();
+
+fn overlay_firmware_map() -> &'static Mutex<HashMap<String, String>> {
+    OVERLAY_FIRMWARE.get_or_init(|| Mutex::new(HashMap::new()))
+}
+
+/// The isolating bridges each applied overlay declared, keyed by overlay handle,
+/// so `remove_overlay` can disable them before tearing the overlay down.
+static OVERLAY_BRIDGES: OnceLock<Mutex<HashMap<String, Vec<String>>>> = OnceLock::new();
+
+fn overlay_bridges_map() -> &'static Mutex<HashMap<String, Vec<String>>> {
+    OVERLAY_BRIDGES.get_or_init(|| Mutex::new(HashMap::new()))
+}
+
+/// Resolves the `fpga-bridges` phandle list declared by the overlay to the
+/// matching bridge handles under [`config::FPGA_BRIDGES_DIR`]. Phandles that map
+/// to no present bridge are skipped.
+fn resolve_overlay_bridges(blob: &[u8]) -> Vec<String> {
+    let phandles = match fdt::fpga_bridges_phandles(blob) {
+        Ok(p) => p,
+        Err(e) => {
+            trace!("could not parse overlay for fpga-bridges: {e}");
+            return Vec::new();
+        }
+    };
+    let available = crate::platforms::platform::list_fpga_bridges().unwrap_or_default();
+    let mut resolved = Vec::new();
+    for phandle in phandles {
+        if let Ok(Some(node_name)) = fdt::node_name_for_phandle(blob, phandle) {
+            let label = node_name.split('@').next().unwrap_or(&node_name);
+            if let Some(bridge) = available
+                .iter()
+                .find(|b| b.contains(label) || node_name.contains(b.as_str()))
+            {
+                resolved.push(bridge.clone());
+            }
+        }
+    }
+    resolved
+}
+
+/// Records the firmware an overlay pulled in, keyed by overlay handle.
+pub fn record_overlay_firmware(overlay_handle: &str, firmware_name: &str) {
+    if let Ok(mut map) = overlay_firmware_map().lock() {
+        map.insert(overlay_handle.to_string(), firmware_name.to_string());
+    }
+}
+
+/// Returns the firmware an applied overlay pulled in, if fpgad recorded one.
+pub fn overlay_firmware(overlay_handle: &str) -> Option<String> {
+    overlay_firmware_map()
+        .lock()
+        .ok()
+        .and_then(|m| m.get(overlay_handle).cloned())
+}
+
+/// Returns the isolating bridge handles an applied overlay declared, as recorded
+/// when it was applied. Empty when the overlay declared none or is not applied.
+pub fn overlay_bridges(overlay_handle: &str) -> Vec<String> {
+    overlay_bridges_map()
+        .lock()
+        .ok()
+        .and_then(|m| m.get(overlay_handle).cloned())
+        .unwrap_or_default()
+}
+
+/// Programming flags understood by the kernel FPGA manager, mirrored here so
+/// that `required_flags` can derive them from overlay metadata. These match the
+/// `FPGA_MGR_*` bits in `include/linux/fpga/fpga-mgr.h`.
+const FPGA_MGR_PARTIAL_RECONFIG: isize = 0x1;
+const FPGA_MGR_EXTERNAL_CONFIG: isize = 0x2;
+const FPGA_MGR_ENCRYPTED_BITSTREAM: isize = 0x4;
+
+/// fpgad-internal bit recording that the overlay ships a compressed bitstream.
+/// It deliberately sits *outside* the kernel flag word (which only occupies the
+/// low `FPGA_MGR_*` bits) so it never collides with a real manager flag such as
+/// [`crate::platforms::platform::ReconfigFlags::ENCRYPTED_USER_KEY`]. fpgad
+/// inflates the image itself (see [`crate::system_io::maybe_decompress`]) and
+/// strips this bit with [`strip_internal_flags`] before any `set_flags`, since
+/// the kernel has no compression bit of its own.
+pub(crate) const FPGAD_COMPRESSED_CONFIG: isize = 1 << 16;
+
+/// Removes fpgad-internal sentinel bits (currently [`FPGAD_COMPRESSED_CONFIG`])
+/// from a `required_flags` result, leaving only the genuine kernel FPGA-manager
+/// flags that may be handed to `set_flags`.
+pub(crate) fn strip_internal_flags(flags: isize) -> isize {
+    flags & !FPGAD_COMPRESSED_CONFIG
+}
 
 /// Takes a handle and creates and stores an appropriate overlay_fs_path in this object.
 /// The overlay_fs_path is static apart from the handle associated with each
@@ -34,6 +133,10 @@ pub struct UniversalOverlayHandler {
     /// The path which points to the overlay virtual filesystem's dir which contains
     /// `path`, `status` and `dtbo` virtual files for overlay control. `dtbo` appears unused?
     overlay_fs_path: PathBuf,
+    /// The `firmware-name` auto-resolved from the overlay's device tree at apply
+    /// time, if the overlay declared one. Used by callers to load the matching
+    /// bitstream without the user having to name it.
+    firmware_name: Mutex<Option<String>>,
 }
 
 impl UniversalOverlayHandler {
@@ -76,6 +179,22 @@ impl UniversalOverlayHandler {
 
         Ok(())
     }
+
+    /// Verifies only the overlay `status` file without inspecting `path`.
+    /// Used by the blob apply mode where nothing is ever written to the `path`
+    /// file, so there is no firmware-relative path to compare against.
+    fn vfs_check_status(&self) -> Result<(), FpgadError> {
+        let status = self.status()?;
+        match status.contains("applied") {
+            true => {
+                info!("overlay status is 'applied'");
+                Ok(())
+            }
+            false => Err(FpgadError::OverlayStatus(format!(
+                "After writing blob to configfs, overlay status does not show 'applied'. Instead it is '{status}'"
+            ))),
+        }
+    }
 }
 
 impl OverlayHandler for UniversalOverlayHandler {
@@ -105,26 +224,198 @@ impl OverlayHandler for UniversalOverlayHandler {
             )));
         }
 
-        match fs_write(&overlay_path_file, false, source_path_rel) {
-            Ok(_) => {
-                trace!("'{source_path_rel}' successfully written to {overlay_path_file:?}");
+        // configfs re-reads the overlay by path, so a gzip-compressed source must
+        // first be inflated to a real file in the firmware directory. The guard
+        // keeps that temporary file alive until the overlay has been applied.
+        // The firmware search path is configurable (see system_config), falling
+        // back to the hardcoded default when the config is unavailable.
+        let firmware_source_dir = config::system_config::firmware_source_dir()
+            .unwrap_or_else(|_| config::FIRMWARE_SOURCE_DIR.to_string());
+        let source_abs = PathBuf::from(&firmware_source_dir).join(source_path_rel);
+
+        // Auto-resolve the firmware-name and the isolating bridges the overlay
+        // declares, so callers can load the matching bitstream without naming it
+        // and so the fabric is gated safely during reconfiguration.
+        let mut overlay_bridges: Vec<String> = Vec::new();
+        if let Ok(bytes) = fs_read_bytes(&source_abs) {
+            let blob = maybe_decompress(bytes)?;
+            match fdt::firmware_name(&blob) {
+                Ok(Some(name)) => {
+                    info!("overlay declares firmware-name '{name}'");
+                    if let Some(handle) = overlay_fs_path.file_name().and_then(|h| h.to_str()) {
+                        record_overlay_firmware(handle, &name);
+                    }
+                    if let Ok(mut g) = self.firmware_name.lock() {
+                        *g = Some(name);
+                    }
+                }
+                Ok(None) => {}
+                Err(e) => trace!("could not parse overlay for firmware-name: {e}"),
+            }
+            overlay_bridges = resolve_overlay_bridges(&blob);
+            if !overlay_bridges.is_empty() {
+                if let Some(handle) = overlay_fs_path.file_name().and_then(|h| h.to_str()) {
+                    if let Ok(mut map) = overlay_bridges_map().lock() {
+                        map.insert(handle.to_string(), overlay_bridges.clone());
+                    }
+                }
             }
-            Err(e) => return Err(e),
         }
-        self.vfs_check_applied(source_path_rel)
+
+        let _decompressed = materialize_if_compressed(&source_abs)?;
+        let effective_rel: String = match &_decompressed {
+            Some(d) => d
+                .path()
+                .strip_prefix(&firmware_source_dir)
+                .unwrap_or(d.path())
+                .to_string_lossy()
+                .into_owned(),
+            None => source_path_rel.to_string(),
+        };
+
+        // Isolate the fabric by disabling the region's bridges before the load,
+        // following the Linux fpga-region model, and re-enable them once the
+        // overlay is confirmed applied. On any failure the bridges are restored
+        // so the fabric is never left stranded.
+        if !overlay_bridges.is_empty() {
+            trace!("disabling overlay bridges {overlay_bridges:?} before apply");
+            universal_bridge::disable_bridges(&overlay_bridges)?;
+        }
+
+        let result = (|| {
+            fs_write(&overlay_path_file, false, &effective_rel)?;
+            trace!("'{effective_rel}' successfully written to {overlay_path_file:?}");
+            self.vfs_check_applied(&effective_rel)
+        })();
+
+        if !overlay_bridges.is_empty() {
+            trace!("re-enabling overlay bridges {overlay_bridges:?}");
+            if let Err(e) = universal_bridge::enable_bridges(&overlay_bridges) {
+                trace!("failed to re-enable overlay bridges after apply: {e}");
+            }
+        }
+        result
     }
 
-    /// Attempts to delete overlay_fs_path
+    /// Applies an overlay directly from an in-memory `.dtbo` blob instead of a
+    /// firmware-relative path. The raw bytes are written to the `dtbo` virtual
+    /// file, which makes the kernel apply the overlay without consulting the
+    /// firmware search path. This allows overlays that do not live under
+    /// `/lib/firmware` (e.g. ones received over D-Bus or built at runtime).
+    fn apply_overlay_blob(&self, dtbo_bytes: &[u8]) -> Result<(), FpgadError> {
+        let overlay_fs_path = self.overlay_fs_path()?;
+        if overlay_fs_path.exists() {
+            return Err(FpgadError::Argument(format!(
+                "Overlay with this handle already exists at {overlay_fs_path:?}. \
+                 Remove the overlay and try again."
+            )));
+        }
+
+        fs_create_dir(overlay_fs_path)?;
+        trace!("Created dir {overlay_fs_path:?}");
+
+        let overlay_dtbo_file = overlay_fs_path.join("dtbo");
+        if !overlay_dtbo_file.exists() {
+            return Err(FpgadError::Internal(format!(
+                "Overlay at {overlay_fs_path:?} did not initialise a new overlay: \
+                the `dtbo` virtual file did not get created by the kernel. \
+                Is the parent dir mounted as a configfs directory?"
+            )));
+        }
+
+        // Decompress in-memory blobs transparently when they are shipped gzipped.
+        let dtbo_bytes = maybe_decompress(dtbo_bytes.to_vec())?;
+        fs_write_bytes(&overlay_dtbo_file, false, &dtbo_bytes)?;
+        trace!("{} blob bytes written to {overlay_dtbo_file:?}", dtbo_bytes.len());
+        self.vfs_check_status()
+    }
+
+    /// Attempts to delete overlay_fs_path, first disabling the bridges the
+    /// overlay isolated at apply time so the fabric is gated while the overlay's
+    /// nodes (and any bitstream) are torn down.
     fn remove_overlay(&self) -> Result<(), FpgadError> {
         let overlay_fs_path = self.overlay_fs_path()?;
+        if let Some(handle) = overlay_fs_path.file_name().and_then(|h| h.to_str()) {
+            let bridges = overlay_bridges_map()
+                .lock()
+                .ok()
+                .and_then(|m| m.get(handle).cloned())
+                .unwrap_or_default();
+            if !bridges.is_empty() {
+                trace!("disabling overlay bridges {bridges:?} before removal");
+                universal_bridge::disable_bridges(&bridges)?;
+            }
+            if let Ok(mut map) = overlay_bridges_map().lock() {
+                map.remove(handle);
+            }
+        }
         fs_remove_dir(overlay_fs_path)
     }
 
-    /// WARNING NOT IMPLEMENTED:
-    /// This is where the required fpga flags will be determined from the dtbo,
-    /// such as compressed or encrypted.
-    fn required_flags(&self) -> Result<isize, FpgadError> {
-        Ok(0)
+    /// Derives the programming flags from the overlay's flattened device tree.
+    ///
+    /// Walks the overlay fragments and, for each node whose `compatible`
+    /// contains `"fpga-region"`, maps the `external-fpga-config`,
+    /// `encrypted-fpga-config`, `partial-fpga-config` and `compressed-fpga-config`
+    /// properties onto the corresponding `FPGA_MGR_*` bits (with `compressed-fpga-config`
+    /// recorded in the out-of-band [`FPGAD_COMPRESSED_CONFIG`] sentinel rather
+    /// than a kernel bit). Falls back to `Ok(0)` when there is no
+    /// fpga-region node or no recognised properties, and surfaces a malformed
+    /// blob as [`FpgadError::OverlayStatus`].
+    fn required_flags(&self, overlay_blob: &[u8]) -> Result<isize, FpgadError> {
+        let blob = maybe_decompress(overlay_blob.to_vec())?;
+        let nodes = fdt::parse_nodes(&blob)?;
+
+        let mut flags: isize = 0;
+        let mut matched = false;
+        for node in &nodes {
+            if !node.compatible_contains("fpga-region") {
+                continue;
+            }
+            matched = true;
+            if node
+                .property("external-fpga-config")
+                .is_some_and(|p| p.is_bool_true())
+            {
+                flags |= FPGA_MGR_EXTERNAL_CONFIG;
+            }
+            if node
+                .property("encrypted-fpga-config")
+                .is_some_and(|p| p.is_bool_true())
+            {
+                flags |= FPGA_MGR_ENCRYPTED_BITSTREAM;
+            }
+            if node
+                .property("partial-fpga-config")
+                .is_some_and(|p| p.is_bool_true())
+            {
+                flags |= FPGA_MGR_PARTIAL_RECONFIG;
+            }
+            if node
+                .property("compressed-fpga-config")
+                .is_some_and(|p| p.is_bool_true())
+            {
+                flags |= FPGAD_COMPRESSED_CONFIG;
+            }
+        }
+
+        if !matched {
+            trace!("no fpga-region node found in overlay, defaulting to flags 0");
+            return Ok(0);
+        }
+        Ok(flags)
+    }
+
+    /// Reads the `firmware-name` declared by the compiled overlay at the
+    /// firmware-relative `source_path`. The source is read from the configured
+    /// firmware directory and gzip-decompressed when needed before its FDT is
+    /// walked for fpga-region metadata.
+    fn overlay_firmware_name(&self, source_path: &Path) -> Result<Option<String>, FpgadError> {
+        let firmware_source_dir = config::system_config::firmware_source_dir()
+            .unwrap_or_else(|_| config::FIRMWARE_SOURCE_DIR.to_string());
+        let source_abs = PathBuf::from(&firmware_source_dir).join(source_path);
+        let blob = maybe_decompress(fs_read_bytes(&source_abs)?)?;
+        fdt::firmware_name(&blob)
     }
 
     /// Read status from <overlay_fs_path>/status file and verify that it is "applied"
@@ -148,6 +439,13 @@ impl UniversalOverlayHandler {
     pub(crate) fn new(overlay_handle: &str) -> Self {
         UniversalOverlayHandler {
             overlay_fs_path: construct_overlay_fs_path(overlay_handle),
+            firmware_name: Mutex::new(None),
         }
     }
+
+    /// The `firmware-name` auto-resolved from the overlay's device tree during
+    /// the last successful apply, if the overlay declared one.
+    pub(crate) fn resolved_firmware_name(&self) -> Option<String> {
+        self.firmware_name.lock().ok().and_then(|g| g.clone())
+    }
 }
@@ -0,0 +1,246 @@
+// This file is part of fpgad, an application to manage FPGA subsystem together with device-tree and kernel modules.
+//
+// Copyright 2025 Canonical Ltd.
+//
+// SPDX-License-Identifier: GPL-3.0-only
+//
+// fpgad is free software: you can redistribute it and/or modify it under the terms of the GNU General Public License version 3, as published by the Free Software Foundation.
+//
+// fpgad is distributed in the hope that it will be useful, but WITHOUT ANY WARRANTY; without even the implied warranties of MERCHANTABILITY, SATISFACTORY QUALITY, or FITNESS FOR A PARTICULAR PURPOSE.  See the GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License along with this program.  If not, see http://www.gnu.org/licenses/.
+
+//! Minimal parser for the Xilinx `xclbin` (axlf) container format.
+//!
+//! An xclbin wraps the raw bitstream together with metadata sections. Field
+//! offsets follow `axlf` / `axlf_header` / `axlf_section_header` from Xilinx's
+//! `xclbin.h`; the container is little-endian. Only enough is parsed to locate
+//! and extract the embedded `BITSTREAM` section so it can be programmed like a
+//! plain `.bit` file.
+
+use crate::error::FpgadError;
+use std::collections::HashMap;
+use std::sync::{Mutex, OnceLock};
+
+const AXLF_MAGIC: &[u8; 7] = b"xclbin2";
+/// Offset of `m_header.m_length` (u64), the total container length.
+const FILE_LENGTH_OFFSET: usize = 224;
+/// Offset of `m_header.uuid` (16 bytes), the design UUID.
+const UUID_OFFSET: usize = 336;
+/// Offset of `m_numSections` (u32) within the container.
+const NUM_SECTIONS_OFFSET: usize = 368;
+/// Offset of the first `axlf_section_header`.
+const SECTIONS_OFFSET: usize = 376;
+/// Size of one `axlf_section_header`.
+const SECTION_HEADER_SIZE: usize = 40;
+/// `axlf_section_kind::BITSTREAM`.
+const SECTION_KIND_BITSTREAM: u32 = 0;
+
+fn read_u32_le(blob: &[u8], off: usize) -> Result<u32, FpgadError> {
+    blob.get(off..off + 4)
+        .map(|b| u32::from_le_bytes([b[0], b[1], b[2], b[3]]))
+        .ok_or_else(|| FpgadError::Argument("xclbin truncated while reading u32".into()))
+}
+
+fn read_u64_le(blob: &[u8], off: usize) -> Result<u64, FpgadError> {
+    blob.get(off..off + 8)
+        .map(|b| u64::from_le_bytes(b.try_into().unwrap()))
+        .ok_or_else(|| FpgadError::Argument("xclbin truncated while reading u64".into()))
+}
+
+/// One section of an xclbin, summarised by its kind and byte size.
+#[derive(Debug, Clone)]
+pub struct SectionInfo {
+    pub kind: u32,
+    pub size: u64,
+}
+
+/// The metadata fpgad keeps about the xclbin last programmed onto a device: the
+/// design UUID and the section-kind/size table from the `axlf` header.
+#[derive(Debug, Clone)]
+pub struct DesignInfo {
+    pub uuid: [u8; 16],
+    pub sections: Vec<SectionInfo>,
+}
+
+impl DesignInfo {
+    /// The design UUID formatted as lowercase hex (no separators).
+    pub fn uuid_string(&self) -> String {
+        self.uuid.iter().map(|b| format!("{b:02x}")).collect()
+    }
+
+    /// A human-readable one-line-per-section summary, prefixed with the UUID.
+    pub fn summary(&self) -> String {
+        let mut out = format!("uuid={}", self.uuid_string());
+        for section in &self.sections {
+            out.push_str(&format!("\nkind={} size={}", section.kind, section.size));
+        }
+        out
+    }
+}
+
+/// The parsed `axlf` metadata of the most recently loaded xclbin per device.
+static LOADED_DESIGNS: OnceLock<Mutex<HashMap<String, DesignInfo>>> = OnceLock::new();
+
+fn loaded_designs() -> &'static Mutex<HashMap<String, DesignInfo>> {
+    LOADED_DESIGNS.get_or_init(|| Mutex::new(HashMap::new()))
+}
+
+/// Caches the design metadata parsed from an xclbin against `device_handle`.
+pub fn record_design_info(device_handle: &str, info: DesignInfo) {
+    if let Ok(mut map) = loaded_designs().lock() {
+        map.insert(device_handle.to_string(), info);
+    }
+}
+
+/// Forgets any cached design metadata for `device_handle`, used when a raw
+/// bitstream with no container metadata is programmed.
+pub fn clear_design_info(device_handle: &str) {
+    if let Ok(mut map) = loaded_designs().lock() {
+        map.remove(device_handle);
+    }
+}
+
+/// Returns the cached design metadata for `device_handle`, or an error when the
+/// last load was a raw bitstream carrying no xclbin container.
+pub fn loaded_design_info(device_handle: &str) -> Result<DesignInfo, FpgadError> {
+    loaded_designs()
+        .lock()
+        .ok()
+        .and_then(|m| m.get(device_handle).cloned())
+        .ok_or_else(|| {
+            FpgadError::Argument(format!(
+                "no xclbin design metadata recorded for {device_handle}; the last load was a raw bitstream"
+            ))
+        })
+}
+
+/// Parses the `axlf` header into the design UUID and section table, without
+/// slicing out any section payload.
+pub fn parse_design_info(blob: &[u8]) -> Result<DesignInfo, FpgadError> {
+    if !is_xclbin(blob) {
+        return Err(FpgadError::Argument(
+            "not an xclbin container (bad magic)".into(),
+        ));
+    }
+    let uuid: [u8; 16] = blob
+        .get(UUID_OFFSET..UUID_OFFSET + 16)
+        .and_then(|b| b.try_into().ok())
+        .ok_or_else(|| FpgadError::Argument("xclbin truncated while reading uuid".into()))?;
+    let num_sections = read_u32_le(blob, NUM_SECTIONS_OFFSET)? as usize;
+    // `num_sections` is an attacker-controlled count; bound it by how many
+    // section headers could possibly fit in the blob before reserving, so a
+    // crafted container cannot request a huge allocation and abort the daemon.
+    let max_sections = blob
+        .len()
+        .saturating_sub(SECTIONS_OFFSET)
+        / SECTION_HEADER_SIZE;
+    if num_sections > max_sections {
+        return Err(FpgadError::Argument(format!(
+            "xclbin num_sections ({num_sections}) exceeds what the container can hold ({max_sections})"
+        )));
+    }
+    let mut sections = Vec::with_capacity(num_sections);
+    for i in 0..num_sections {
+        let hdr = SECTIONS_OFFSET + i * SECTION_HEADER_SIZE;
+        let kind = read_u32_le(blob, hdr)?;
+        let size = read_u64_le(blob, hdr + 32)?;
+        sections.push(SectionInfo { kind, size });
+    }
+    Ok(DesignInfo { uuid, sections })
+}
+
+/// Returns true if `blob` starts with the xclbin magic.
+pub fn is_xclbin(blob: &[u8]) -> bool {
+    blob.len() >= AXLF_MAGIC.len() && &blob[..AXLF_MAGIC.len()] == AXLF_MAGIC
+}
+
+/// Extracts the raw BITSTREAM section from an xclbin container.
+pub fn extract_bitstream(blob: &[u8]) -> Result<Vec<u8>, FpgadError> {
+    if !is_xclbin(blob) {
+        return Err(FpgadError::Argument(
+            "not an xclbin container (bad magic)".into(),
+        ));
+    }
+    let file_length = read_u64_le(blob, FILE_LENGTH_OFFSET)? as usize;
+    if file_length != blob.len() {
+        return Err(FpgadError::Argument(format!(
+            "xclbin file_length ({file_length}) does not match actual length ({})",
+            blob.len()
+        )));
+    }
+    let num_sections = read_u32_le(blob, NUM_SECTIONS_OFFSET)? as usize;
+    for i in 0..num_sections {
+        let hdr = SECTIONS_OFFSET + i * SECTION_HEADER_SIZE;
+        let kind = read_u32_le(blob, hdr)?;
+        if kind != SECTION_KIND_BITSTREAM {
+            continue;
+        }
+        let offset = read_u64_le(blob, hdr + 24)? as usize;
+        let size = read_u64_le(blob, hdr + 32)? as usize;
+        if size == 0 {
+            return Err(FpgadError::Argument(
+                "xclbin BITSTREAM section is empty".into(),
+            ));
+        }
+        let end = offset
+            .checked_add(size)
+            .ok_or_else(|| FpgadError::Argument("xclbin BITSTREAM section overflows".into()))?;
+        return blob.get(offset..end).map(<[u8]>::to_vec).ok_or_else(|| {
+            FpgadError::Argument("xclbin BITSTREAM section runs past end of file".into())
+        });
+    }
+    Err(FpgadError::Argument(
+        "xclbin container has no BITSTREAM section".into(),
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Builds a minimal `axlf` header of `SECTIONS_OFFSET` bytes with only the
+    /// magic and `num_sections` fields set, followed by `trailing_bytes` zero
+    /// bytes.
+    fn header_with_sections(num_sections: u32, trailing_bytes: usize) -> Vec<u8> {
+        let mut blob = vec![0u8; SECTIONS_OFFSET + trailing_bytes];
+        blob[..AXLF_MAGIC.len()].copy_from_slice(AXLF_MAGIC);
+        blob[NUM_SECTIONS_OFFSET..NUM_SECTIONS_OFFSET + 4]
+            .copy_from_slice(&num_sections.to_le_bytes());
+        blob
+    }
+
+    #[test]
+    fn parse_design_info_rejects_num_sections_that_overflow_the_container() {
+        // Declares 1000 sections but ships none of the section-header bytes a
+        // real container of this size would need to back them.
+        let blob = header_with_sections(1000, 0);
+
+        let err = parse_design_info(&blob).unwrap_err();
+
+        assert!(err.to_string().contains("num_sections"));
+    }
+
+    #[test]
+    fn parse_design_info_accepts_a_num_sections_that_fits() {
+        let mut blob = header_with_sections(1, SECTION_HEADER_SIZE);
+        let hdr = SECTIONS_OFFSET;
+        blob[hdr..hdr + 4].copy_from_slice(&SECTION_KIND_BITSTREAM.to_le_bytes());
+        blob[hdr + 32..hdr + 40].copy_from_slice(&42u64.to_le_bytes());
+
+        let info = parse_design_info(&blob).unwrap();
+
+        assert_eq!(info.sections.len(), 1);
+        assert_eq!(info.sections[0].kind, SECTION_KIND_BITSTREAM);
+        assert_eq!(info.sections[0].size, 42);
+    }
+
+    #[test]
+    fn parse_design_info_rejects_bad_magic() {
+        let blob = vec![0u8; SECTIONS_OFFSET];
+
+        let err = parse_design_info(&blob).unwrap_err();
+
+        assert!(err.to_string().contains("bad magic"));
+    }
+}
@@ -46,25 +46,21 @@ impl Platform for XilinxDfxMgrPlatform {
 }
 
 /// List locally downloaded accelerator packages
-#[allow(dead_code)]
 pub fn list_package() -> Result<String, FpgadSoftenerError> {
     run_dfx_mgr(&["-listPackage"])
 }
 
 /// Load the provided accelerator package
-#[allow(dead_code)]
 pub fn load(accel_name: &str) -> Result<String, FpgadSoftenerError> {
     run_dfx_mgr(&["-load", accel_name])
 }
 
 /// Unload package previously programmed
-#[allow(dead_code)]
 pub fn remove(slot: u32) -> Result<String, FpgadSoftenerError> {
     run_dfx_mgr(&["-remove", &slot.to_string()])
 }
 
 /// List accelerator UIOs
-#[allow(dead_code)]
 pub fn list_uio(slot: Option<u32>, uio_name: Option<&str>) -> Result<String, FpgadSoftenerError> {
     let mut args = vec!["-listUIO"];
     if let Some(name) = uio_name {
@@ -80,7 +76,6 @@ pub fn list_uio(slot: Option<u32>, uio_name: Option<&str>) -> Result<String, Fpg
 }
 
 /// List inter-RM buffer info
-#[allow(dead_code)]
 pub fn list_irbuf(slot: Option<u32>) -> Result<String, FpgadSoftenerError> {
     let mut args = vec!["-listIRbuf"];
     if let Some(slot) = slot {
@@ -93,31 +88,26 @@ pub fn list_irbuf(slot: Option<u32>) -> Result<String, FpgadSoftenerError> {
 }
 
 /// Set RM stream from slot a to b
-#[allow(dead_code)]
 pub fn set_irbuf(a: u32, b: u32) -> Result<String, FpgadSoftenerError> {
     run_dfx_mgr(&["-setIRbuf", &format!("{a},{b}")])
 }
 
 /// Allocate buffer of size and return its DMA fd and pa
-#[allow(dead_code)]
 pub fn alloc_buffer(size: u64) -> Result<String, FpgadSoftenerError> {
     run_dfx_mgr(&["-allocBuffer", &size.to_string()])
 }
 
 /// Free buffer with physical address pa in decimal
-#[allow(dead_code)]
 pub fn free_buffer(pa: u64) -> Result<String, FpgadSoftenerError> {
     run_dfx_mgr(&["-freeBuffer", &pa.to_string()])
 }
 
 /// Send ip device FD's over socket
-#[allow(dead_code)]
 pub fn get_fds(slot: u32) -> Result<String, FpgadSoftenerError> {
     run_dfx_mgr(&["-getFDs", &slot.to_string()])
 }
 
 /// Get RM info
-#[allow(dead_code)]
 pub fn get_rm_info() -> Result<String, FpgadSoftenerError> {
     run_dfx_mgr(&["-getRMInfo"])
 }
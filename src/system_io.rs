@@ -12,12 +12,182 @@
 
 use crate::config;
 use crate::error::FpgadError;
+use flate2::read::GzDecoder;
 use log::trace;
 use std::fs::OpenOptions;
-use std::fs::{create_dir_all, remove_dir};
+use std::fs::{create_dir_all, remove_dir, remove_file};
 use std::io::{Read, Write};
 use std::path::{Path, PathBuf};
 
+/// The two leading bytes every gzip stream begins with.
+const GZIP_MAGIC: [u8; 2] = [0x1f, 0x8b];
+/// The four-byte zstandard frame magic (little-endian `0xFD2FB528`).
+const ZSTD_MAGIC: [u8; 4] = [0x28, 0xb5, 0x2f, 0xfd];
+/// The six-byte `.xz` stream header magic.
+const XZ_MAGIC: [u8; 6] = [0xfd, 0x37, 0x7a, 0x58, 0x5a, 0x00];
+
+/// The compression format of a firmware/overlay image, detected from its magic
+/// bytes rather than a filename extension. FPGA bitstreams are mostly zeros and
+/// so ship compressed; the loader inflates them transparently before handing
+/// them to the kernel.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Compression {
+    None,
+    Gzip,
+    Zstd,
+    Xz,
+}
+
+impl Compression {
+    /// Classifies `bytes` by leading magic number.
+    fn detect(bytes: &[u8]) -> Compression {
+        if bytes.len() >= 2 && bytes[..2] == GZIP_MAGIC {
+            Compression::Gzip
+        } else if bytes.len() >= 4 && bytes[..4] == ZSTD_MAGIC {
+            Compression::Zstd
+        } else if bytes.len() >= 6 && bytes[..6] == XZ_MAGIC {
+            Compression::Xz
+        } else {
+            Compression::None
+        }
+    }
+}
+
+/// Largest inflated size accepted from a compressed image. FPGA bitstreams are
+/// mostly zeros and so compress dramatically, but an unbounded inflate of a
+/// hostile or corrupt stream could exhaust memory; anything above this is
+/// rejected as an implausible expansion.
+const MAX_DECOMPRESSED_BYTES: u64 = 512 * 1024 * 1024;
+
+/// Largest errno the platform's error table is expected to cover. Some FPGA
+/// manager drivers (notably Xilinx) return error codes whose absolute value is
+/// far larger than any real errno; formatting such an [`std::io::Error`] makes
+/// the stdlib index its errno string table out of bounds and panic. Anything
+/// above this is treated as a driver status code, not a libc errno.
+const MAX_ERRNO: i32 = 4095;
+
+/// Replaces an [`std::io::Error`] carrying an out-of-range OS error code with a
+/// sanitized one whose message preserves the raw code, so it can be formatted
+/// and propagated instead of panicking when the stdlib looks up the errno. In
+/// range (or non-OS) errors are returned untouched.
+fn sanitize_os_error(e: std::io::Error) -> std::io::Error {
+    match e.raw_os_error() {
+        Some(code) if code.unsigned_abs() > MAX_ERRNO as u32 => std::io::Error::other(format!(
+            "driver returned out-of-range error code {code}"
+        )),
+        _ => e,
+    }
+}
+
+/// Returns true when `bytes` begins with a recognised compression magic (gzip,
+/// zstd or xz), i.e. [`maybe_decompress`] would inflate it.
+pub fn is_compressed(bytes: &[u8]) -> bool {
+    Compression::detect(bytes) != Compression::None
+}
+
+/// Convenient wrapper for reading the raw bytes of `file_path`.
+pub fn fs_read_bytes(file_path: &Path) -> Result<Vec<u8>, FpgadError> {
+    trace!("Attempting to read bytes from {file_path:?}");
+    let mut buf: Vec<u8> = Vec::new();
+    let result = OpenOptions::new()
+        .read(true)
+        .open(file_path)
+        .and_then(|mut f| f.read_to_end(&mut buf));
+    match result {
+        Ok(_) => {
+            trace!("Reading done ({} bytes)", buf.len());
+            Ok(buf)
+        }
+        Err(e) => Err(FpgadError::IORead {
+            file: file_path.into(),
+            e,
+        }),
+    }
+}
+
+/// If `bytes` is a compressed stream (gzip, zstd or xz, detected by content and
+/// not extension), inflate it and return the decompressed buffer; otherwise
+/// return the input unchanged.
+pub fn maybe_decompress(bytes: Vec<u8>) -> Result<Vec<u8>, FpgadError> {
+    let format = Compression::detect(&bytes);
+    if format == Compression::None {
+        return Ok(bytes);
+    }
+    trace!("{format:?} magic detected, inflating {} bytes", bytes.len());
+    // Bound the inflate so a corrupt or hostile stream cannot exhaust memory:
+    // read one byte past the cap and treat overflow as an implausible expansion.
+    let reader: Box<dyn Read> = match format {
+        Compression::Gzip => Box::new(GzDecoder::new(bytes.as_slice())),
+        Compression::Zstd => Box::new(zstd::stream::read::Decoder::new(bytes.as_slice()).map_err(
+            |e| FpgadError::IORead {
+                file: PathBuf::from("<zstd stream>"),
+                e,
+            },
+        )?),
+        Compression::Xz => Box::new(xz2::read::XzDecoder::new(bytes.as_slice())),
+        Compression::None => unreachable!("guarded above"),
+    };
+    let mut out = Vec::new();
+    reader
+        .take(MAX_DECOMPRESSED_BYTES + 1)
+        .read_to_end(&mut out)
+        .map_err(|e| FpgadError::IORead {
+            file: PathBuf::from("<compressed stream>"),
+            e,
+        })?;
+    if out.len() as u64 > MAX_DECOMPRESSED_BYTES {
+        return Err(FpgadError::Argument(format!(
+            "refusing to decompress image: inflated size exceeds {MAX_DECOMPRESSED_BYTES} bytes"
+        )));
+    }
+    trace!("inflated to {} bytes", out.len());
+    Ok(out)
+}
+
+/// A decompressed overlay materialized next to its source so that configfs can
+/// re-read it by path. The temporary file is removed on drop.
+#[derive(Debug)]
+pub struct DecompressedOverlay {
+    path: PathBuf,
+    /// Size in bytes of the inflated overlay.
+    pub decompressed_size: usize,
+}
+
+impl DecompressedOverlay {
+    /// Path to the materialized `.dtbo`.
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+}
+
+impl Drop for DecompressedOverlay {
+    fn drop(&mut self) {
+        if let Err(e) = remove_file(&self.path) {
+            trace!("Could not remove temporary overlay {:?}: {e}", self.path);
+        }
+    }
+}
+
+/// Reads `source_path`, and if it is compressed (gzip, zstd or xz), inflates it
+/// to a sibling temporary `.dtbo` file and returns a [`DecompressedOverlay`]
+/// guard that cleans the file up when dropped. Returns `Ok(None)` when the
+/// source is not compressed and can be used as-is (the zero-copy path).
+pub fn materialize_if_compressed(
+    source_path: &Path,
+) -> Result<Option<DecompressedOverlay>, FpgadError> {
+    let bytes = fs_read_bytes(source_path)?;
+    if !is_compressed(&bytes) {
+        return Ok(None);
+    }
+    let inflated = maybe_decompress(bytes)?;
+    let temp_path = source_path.with_extension("decompressed.dtbo");
+    fs_write_bytes(&temp_path, true, &inflated)?;
+    Ok(Some(DecompressedOverlay {
+        path: temp_path,
+        decompressed_size: inflated.len(),
+    }))
+}
+
 /// Convenient wrapper for reading the contents of `file_path` to String
 pub fn fs_read(file_path: &Path) -> Result<String, FpgadError> {
     trace!("Attempting to read from {file_path:?}");
@@ -60,11 +230,98 @@ pub fn fs_write(file_path: &Path, create: bool, value: impl AsRef<str>) -> Resul
         Err(e) => Err(FpgadError::IOWrite {
             data: value.as_ref().to_string(),
             file: file_path.into(),
-            e,
+            e: sanitize_os_error(e),
         }),
     }
 }
 
+/// Convenient wrapper for writing raw `bytes` to `file_path`.
+/// Needed for binary payloads (e.g. a raw `.dtbo` blob) that are not valid UTF-8
+/// and so cannot go through [`fs_write`].
+pub fn fs_write_bytes(file_path: &Path, create: bool, bytes: &[u8]) -> Result<(), FpgadError> {
+    trace!("Attempting to write {} bytes to {:?}", bytes.len(), file_path);
+    let result = OpenOptions::new()
+        .create(create)
+        .read(false)
+        .write(true)
+        .open(file_path)
+        .and_then(|mut f| f.write_all(bytes));
+    match result {
+        Ok(_) => {
+            trace!("Write done.");
+            Ok(())
+        }
+        Err(e) => Err(FpgadError::IOWrite {
+            data: format!("<{} bytes>", bytes.len()),
+            file: file_path.into(),
+            e: sanitize_os_error(e),
+        }),
+    }
+}
+
+/// Appends `bytes` to `file_path`, creating it if necessary. Used to stream a
+/// large bitstream to disk in chunks without holding the whole image in memory.
+pub fn fs_append_bytes(file_path: &Path, bytes: &[u8]) -> Result<(), FpgadError> {
+    trace!("Appending {} bytes to {:?}", bytes.len(), file_path);
+    let result = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(file_path)
+        .and_then(|mut f| f.write_all(bytes));
+    match result {
+        Ok(_) => Ok(()),
+        Err(e) => Err(FpgadError::IOWrite {
+            data: format!("<{} bytes>", bytes.len()),
+            file: file_path.into(),
+            e: sanitize_os_error(e),
+        }),
+    }
+}
+
+/// Streams `src` to `dst` in fixed-size chunks so a multi-megabyte FPGA image
+/// never fully materializes in memory. Mirrors the kernel's contiguous-buffer
+/// programming path, where the image is fed to the manager a block at a time.
+pub fn fs_copy_to(src: &Path, dst: &Path) -> Result<(), FpgadError> {
+    const CHUNK: usize = 64 * 1024;
+    trace!("Streaming {src:?} to {dst:?} in {CHUNK} byte chunks");
+    let mut reader = OpenOptions::new()
+        .read(true)
+        .open(src)
+        .map_err(|e| FpgadError::IORead {
+            file: src.into(),
+            e,
+        })?;
+    let mut writer = OpenOptions::new()
+        .create(true)
+        .write(true)
+        .truncate(true)
+        .open(dst)
+        .map_err(|e| FpgadError::IOWrite {
+            data: format!("<stream from {src:?}>"),
+            file: dst.into(),
+            e,
+        })?;
+    let mut buf = vec![0u8; CHUNK];
+    loop {
+        let n = reader.read(&mut buf).map_err(|e| FpgadError::IORead {
+            file: src.into(),
+            e,
+        })?;
+        if n == 0 {
+            break;
+        }
+        writer
+            .write_all(&buf[..n])
+            .map_err(|e| FpgadError::IOWrite {
+                data: format!("<{n} bytes>"),
+                file: dst.into(),
+                e,
+            })?;
+    }
+    trace!("Streaming copy done.");
+    Ok(())
+}
+
 /// Convenient wrapper for recursively creating directories up to `path`
 pub fn fs_create_dir(path: &Path) -> Result<(), FpgadError> {
     trace!("Attempting to Create '{path:?}'");
@@ -179,3 +436,50 @@ pub fn fs_read_dir(dir: &Path) -> Result<Vec<String>, FpgadError> {
         },
     )
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use flate2::write::GzEncoder;
+    use flate2::Compression as GzCompression;
+
+    #[test]
+    fn maybe_decompress_inflates_a_gzip_stream() {
+        let mut encoder = GzEncoder::new(Vec::new(), GzCompression::fast());
+        encoder.write_all(b"fpga bitstream payload").unwrap();
+        let compressed = encoder.finish().unwrap();
+
+        let inflated = maybe_decompress(compressed).unwrap();
+
+        assert_eq!(inflated, b"fpga bitstream payload");
+    }
+
+    #[test]
+    fn maybe_decompress_passes_through_uncompressed_bytes_unchanged() {
+        let bytes = b"not compressed at all".to_vec();
+
+        let out = maybe_decompress(bytes.clone()).unwrap();
+
+        assert_eq!(out, bytes);
+    }
+
+    #[test]
+    fn maybe_decompress_rejects_a_decompression_bomb() {
+        // An all-zero plaintext compresses to a tiny gzip stream but inflates
+        // past the cap, pinning the bound that keeps a hostile/corrupt image
+        // from exhausting memory.
+        let mut encoder = GzEncoder::new(Vec::new(), GzCompression::fast());
+        let mut remaining = MAX_DECOMPRESSED_BYTES + 4096;
+        let zeros = [0u8; 64 * 1024];
+        while remaining > 0 {
+            let n = remaining.min(zeros.len() as u64) as usize;
+            encoder.write_all(&zeros[..n]).unwrap();
+            remaining -= n as u64;
+        }
+        let compressed = encoder.finish().unwrap();
+
+        let err = maybe_decompress(compressed).unwrap_err();
+
+        assert!(err.to_string().contains("exceeds"));
+    }
+}